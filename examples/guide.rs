@@ -4,20 +4,66 @@
 
 fn main() {
     sylveon::parse! {
+        //! Inner doc comments describe the whole program.
+        //!
+        //! Inner attributes can also add examples, shown with `--help`, and text shown after the
+        //! help message.
+        #![example("guide rm --dev src", "Remove the source directory")]
+        #![after_help = "See the crate's repository for more."]
+
         // # Option definitions
 
         ignore?,    // optional value (Option<String>)
         all,        // switch (bool)
         verbose+,   // count (usize)
+        tags*,      // list of values, split on commas (Vec<String>)
+        size?[2],   // fixed number of values (Option<[String; 2]>)
+
+        // Optional values are only read when attached, as in `--color=never`, and otherwise use
+        // the given value, so `--color` is the same as `--color=always` (Option<String>).
+        color?("always"),
+
+        // Maps read `key=value` entries (BTreeMap<String, String>). Later keys replace earlier
+        // ones, unless given `#[duplicates = "first"]` or `#[duplicates = "error"]`.
+        define{},
 
         // Option names will default to the variable's name, but can be given explicitly.
         force = f | force,
 
-        // Options can also be documented with triple-slashes.
+        // Options can also be documented with triple-slashes. `-h` only shows the first paragraph,
+        // while `--help` shows all of it.
 
         /// Display version and exit
+        ///
+        /// The version is read from the package
+        /// manifest at build time.
         version,
 
+        // Lists can be split on another delimiter, and the placeholder in the help message can be
+        // renamed for any option taking a value, e.g. `--path <dir:...>`. Options with several
+        // values can name each of them, as in `#[value_name = "width height"]`.
+        #[delimiter = ':']
+        #[value_name = "dir"]
+        path*,
+
+        // Values can be checked with `#[validate(f)]`, where `f` takes a `&str` and returns a
+        // `Result`. Errors are reported as `invalid value '80' for '--port': ...`, and this also
+        // works on positional arguments.
+        #[validate(|v| v.parse::<u16>())]
+        port?,
+
+        // Values can also be converted with `#[parse(f)]`, giving typed options, e.g. an
+        // `Option<Duration>` here. `sylveon::value` has parsers for common kinds of values. Maps
+        // validate and convert the value of each entry, without the key.
+        #[parse(sylveon::value::duration)]
+        timeout?,
+
+        // Options and subcommands can be hidden from the help message with `#[hidden]`.
+        // Deprecated options still work, but print a warning when used.
+        #[hidden]
+        #[deprecated_opt(note = "use --all instead")]
+        every,
+
         // # Positional arguments
 
         // _ => todo!(),        // No arguments
@@ -28,11 +74,16 @@ fn main() {
         // any subcommands.
         "check" | "c" => todo!(),
 
-        // They can also have nested definitions, and even omit the body.
+        // They can also have nested definitions, and even omit the body. Examples can be added to
+        // subcommands as well.
+        #[example("guide rm --dev src")]
         "remove" | "rm" {
             dev,
             path => todo!(),
         }
+
+        // A `help` subcommand is added automatically, so `guide help rm` is the same as
+        // `guide rm --help`. `--help-json` prints the whole command tree as JSON, for other tools.
     }
 }
 
@@ -0,0 +1,40 @@
+//! Example generating a Markdown, HTML or JSON reference for the whole command tree. Outside of a
+//! command, e.g. in a build script, `sylveon::reference!` takes the same definitions instead.
+
+fn main() {
+    let mut args = sylveon::Args::new();
+
+    sylveon::parse! { args;
+        //! A tiny package manager
+        #![example("reference add --dev serde")]
+
+        /// Increase output
+        verbose+,
+
+        /// Add a dependency
+        "add" {
+            /// Add as a development dependency
+            dev,
+            name => todo!(),
+        },
+        /// Remove a dependency
+        "remove" | "rm" {
+            name => todo!(),
+        },
+
+        // The reference can then be generated with `reference docs > cli.md`.
+        #[hidden]
+        "docs" {
+            html,
+            json,
+            _ => {
+                let mut out = std::io::stdout().lock();
+                match (html, json) {
+                    (true, _) => args.write_html(&mut out).unwrap(),
+                    (_, true) => args.write_json(&mut out).unwrap(),
+                    _ => args.write_markdown(&mut out).unwrap(),
+                }
+            }
+        }
+    }
+}
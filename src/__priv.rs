@@ -3,39 +3,125 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-pub use crate::{Args, Error, Opt};
+use std::collections::BTreeMap;
+
+pub use crate::parser::{Command, Duplicates, Kind, Usage};
+pub use crate::{Args, Error, Opt, Verbosity};
 pub use sylveon_macros::opt as __opt;
 
+/// Split a `key=value` entry given to a map option, and insert it into `map` after validating and
+/// converting the value with `convert`. Used by [`parse`](crate::parse).
+///
+/// # Errors
+///
+/// If the value has no `=` or `convert` fails, or if the key was already given and `duplicates`
+/// is [`Duplicates::Error`].
+pub fn map_value<T>(
+    option: &str,
+    value: String,
+    map: &mut BTreeMap<String, T>,
+    duplicates: Duplicates,
+    convert: impl FnOnce(String) -> Result<T, Error>,
+) -> Result<(), Error> {
+    let invalid = |message: String| Error::Invalid {
+        option: option.to_owned(),
+        value: value.clone(),
+        message,
+    };
+
+    let Some((key, entry)) = value.split_once('=') else {
+        return Err(invalid("expected 'key=value'".into()));
+    };
+
+    let entry = convert(entry.to_owned())?;
+    match (map.contains_key(key), duplicates) {
+        (true, Duplicates::First) => {}
+        (true, Duplicates::Error) => return Err(invalid(format!("'{key}' was already given"))),
+        _ => {
+            map.insert(key.to_owned(), entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a `#[validate]` function on a value. Used by [`parse`](crate::parse).
+pub fn validate<T, E: std::fmt::Display>(
+    value: &str,
+    f: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<(), String> {
+    parse(value, f).map(|_| ())
+}
+
+/// Run a `#[parse]` function on a value. Used by [`parse`](crate::parse).
+pub fn parse<T, E: std::fmt::Display>(
+    value: &str,
+    f: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, String> {
+    f(value).map_err(|e| e.to_string())
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __init {
-    ($(#[$attr:meta])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        let mut $opt = None;
+    (#![$($_:tt)*] $($rest:tt)*) => {
+        $crate::__init! { $($rest)* }
+    };
+    ($(#[$($attr:tt)*])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = None;
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        let mut $opt = 0;
+    ($(#[$($attr:tt)*])* $opt:ident?($implicit:literal) $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = None;
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        let mut $opt = false;
+    ($(#[$($attr:tt)*])* $opt:ident{} $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = ::std::collections::BTreeMap::new();
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* $bind:ident => $body:expr $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        let mut $bind = None;
+    ($(#[$($attr:tt)*])* $opt:ident?[$n:literal] $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = None;
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* ..$var:ident => $body:expr $(, $($rest:tt)*)?) => {
+    ($(#[$($attr:tt)*])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = 0;
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* $cmd:literal $(| $cmd2:literal)* $({ $($params:tt)* })? $(=> $body:expr)? $(, $($rest:tt)*)?) => {
+    ($(#[$($attr:tt)*])* $opt:ident* $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = Vec::new();
+        }
+        $crate::__init! { $($($rest)*)* }
+    };
+    ($(#[$($attr:tt)*])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $opt = false;
+        }
+        $crate::__init! { $($($rest)*)* }
+    };
+    ($(#[$($attr:tt)*])* $bind:ident => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            let mut $bind = None;
+        }
         $crate::__init! { $($($rest)*)* }
     };
-    ($(#[$attr:meta])* _ => $body:expr $(, $($rest:tt)*)?) => {
+    ($(#[$($attr:tt)*])* ..$var:ident => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__init! { $($($rest)*)* }
+    };
+    ($(#[$($attr:tt)*])* $cmd:literal $(| $cmd2:literal)* $({ $($params:tt)* })? $(=> $body:expr)? $(, $($rest:tt)*)?) => {
+        $crate::__init! { $($($rest)*)* }
+    };
+    ($(#[$($attr:tt)*])* _ => $body:expr $(, $($rest:tt)*)?) => {
         $crate::__init! { $($($rest)*)* }
     };
     () => {};
@@ -46,7 +132,10 @@ macro_rules! __init {
 macro_rules! __loop {
     ($args:ident; $($rest:tt)*) => {
         loop {
-            let __arg = $args.next_opt();
+            let __arg = match $args.try_next_opt() {
+                Ok(arg) => arg,
+                Err(e) => $crate::__fail!($args, e),
+            };
             $crate::__match! { $args, __arg; $($rest)* }
         }
     };
@@ -55,58 +144,178 @@ macro_rules! __loop {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __match {
-    ($args:ident, $arg:ident; $(#[$attr:meta])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
-            $opt = match $args.value() {
-                Some(v) => Some(v),
-                None => break Err(Error::MissingValue),
-            };
+    ($args:ident, $arg:ident; #![$($_:tt)*] $($rest:tt)*) => {
+        $crate::__match! { $args, $arg; $($rest)* }
+    };
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+                let __value = match $args.value() {
+                    Some(v) => v,
+                    None => $crate::__fail!($args, Error::MissingValue),
+                };
+                $opt = match $crate::__convert! { __opt, __value; $(#[$($attr)*])* } {
+                    Ok(v) => Some(v),
+                    Err(e) => $crate::__fail!($args, e),
+                };
 
-            continue;
+                continue;
+            }
         }
 
         $crate::__match! { $args, $arg; $($($rest)*)* }
     };
-    ($args:ident, $arg:ident; $(#[$attr:meta])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
-            $opt += 1;
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident?($implicit:literal) $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+                let __value = $args.attached_value().unwrap_or_else(|| String::from($implicit));
+                $opt = match $crate::__convert! { __opt, __value; $(#[$($attr)*])* } {
+                    Ok(v) => Some(v),
+                    Err(e) => $crate::__fail!($args, e),
+                };
 
-            continue;
+                continue;
+            }
         }
 
         $crate::__match! { $args, $arg; $($($rest)*)* }
     };
-    ($args:ident, $arg:ident; $(#[$attr:meta])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $(#[$attr])*
-        if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
-            $opt = true;
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident{} $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+                let __value = match $args.value() {
+                    Some(v) => v,
+                    None => $crate::__fail!($args, Error::MissingValue),
+                };
+                let __duplicates = $crate::__duplicates! { $(#[$($attr)*])* };
+                let __result = $crate::__priv::map_value(&__opt, __value, &mut $opt, __duplicates, |v| {
+                    $crate::__convert! { __opt, v; $(#[$($attr)*])* }
+                });
+                if let Err(e) = __result {
+                    $crate::__fail!($args, e);
+                }
 
-            continue;
+                continue;
+            }
+        }
+
+        $crate::__match! { $args, $arg; $($($rest)*)* }
+    };
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident?[$n:literal] $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+                let __values = match $args.values::<$n>() {
+                    Ok(v) => v,
+                    Err(found) => $crate::__fail!($args, Error::MissingValues { option: __opt, expected: $n, found }),
+                };
+                let __values = __values.into_iter().map(|v| $crate::__convert! { __opt, v; $(#[$($attr)*])* });
+                $opt = match __values.collect::<Result<Vec<_>, Error>>() {
+                    Ok(v) => Some(::std::convert::TryInto::<[_; $n]>::try_into(v).unwrap_or_else(|_| unreachable!())),
+                    Err(e) => $crate::__fail!($args, e),
+                };
+
+                continue;
+            }
+        }
+
+        $crate::__match! { $args, $arg; $($($rest)*)* }
+    };
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                $opt += 1;
+
+                continue;
+            }
+        }
+
+        $crate::__match! { $args, $arg; $($($rest)*)* }
+    };
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident* $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+                let __values = match $args.list_value($crate::__delimiter! { $(#[$($attr)*])* }) {
+                    Some(values) => values,
+                    None => $crate::__fail!($args, Error::MissingValue),
+                };
+                let __values = __values.into_iter().map(|v| $crate::__convert! { __opt, v; $(#[$($attr)*])* });
+                match __values.collect::<Result<Vec<_>, Error>>() {
+                    Ok(values) => $opt.extend(values),
+                    Err(e) => $crate::__fail!($args, e),
+                }
+
+                continue;
+            }
+        }
+
+        $crate::__match! { $args, $arg; $($($rest)*)* }
+    };
+    ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let $crate::__pat! { $opt $(= $($v)|*)* } = $arg {
+                $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+                $opt = true;
+
+                continue;
+            }
         }
 
         $crate::__match! { $args, $arg; $($($rest)*)* }
     };
     ($args:ident, $arg:ident; $($rest:tt)+) => {
-        if let Some(Opt::Long("help") | Opt::Short('h')) = $arg {
-            break Err(Error::Help);
+        if let Some(Opt::Short('h')) = $arg {
+            break Err(Error::Help(Verbosity::Short));
+        }
+        if let Some(Opt::Long("help")) = $arg {
+            break Err(Error::Help(Verbosity::Long));
+        }
+        if let Some(Opt::Long("help-json")) = $arg {
+            break Err(Error::HelpJson);
         }
 
         let __val = match $arg {
             Some(Opt::Value(v)) => Some(v.to_owned()),
-            Some(v) => break Err(Error::Unexpected(v.to_string())),
+            Some(v) => $crate::__fail!($args, Error::Unexpected(v.to_string())),
             None => None,
         };
+        let __val = match __val.map(|v| $args.expand_command(v)).transpose() {
+            Ok(v) => v,
+            Err(e) => $crate::__fail!($args, e),
+        };
+        // before any fallback, which would take it as a value
+        if __val.as_deref() == Some("help") && $args.has_help_command() {
+            $args.request_help();
+            continue;
+        }
 
         $crate::__cmd! { $args, __val; $($rest)* }
     };
     ($args:ident, $arg:ident;) => {
-        if let Some(Opt::Long("help") | Opt::Short('h')) = $arg {
-            break Err(Error::Help);
+        if let Some(Opt::Short('h')) = $arg {
+            break Err(Error::Help(Verbosity::Short));
+        }
+        if let Some(Opt::Long("help")) = $arg {
+            break Err(Error::Help(Verbosity::Long));
+        }
+        if let Some(Opt::Long("help-json")) = $arg {
+            break Err(Error::HelpJson);
         }
 
-        break $arg.map_or(Ok(()), |v| Err(Error::Unexpected(v.to_string())));
+        if let Some(v) = $arg {
+            $crate::__fail!($args, Error::Unexpected(v.to_string()));
+        }
+        break $args.take_errors();
     };
 }
 
@@ -114,64 +323,89 @@ macro_rules! __match {
 #[macro_export]
 macro_rules! __cmd {
     ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $bind:ident => $body:expr $(, $($rest:tt)*)?) => {
-        $(#[$($attr)*])*
-        {
-            match $arg {
-                Some(v) if $bind.is_none() => $bind = Some(v),
-                Some(v) => break Err(Error::Unexpected(v)),
-                None => break Ok($body),
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            {
+                match $arg {
+                    Some(v) if $bind.is_none() => match $crate::__convert! { stringify!($bind), v; $(#[$($attr)*])* } {
+                        Ok(v) => $bind = Some(v),
+                        Err(e) => $crate::__fail!($args, e),
+                    },
+                    Some(v) => $crate::__fail!($args, Error::Unexpected(v)),
+                    None => match $args.take_errors() {
+                        Ok(()) => break Ok($body),
+                        Err(e) => break Err(e),
+                    },
+                }
+                continue;
             }
-            continue;
         }
 
         $crate::__cmd! { $args, $arg; $($($rest)*)* }
     };
     ($args:ident, $arg:ident; $(#[$($attr:tt)*])* ..$var:ident => $body:expr $(, $($rest:tt)*)?) => {
-        $(#[$($attr)*])*
-        {
-            let $var = match $args.into_values() {
-                Ok(mut var) => {
-                    if let Some(v) = $arg {
-                        var.insert(0, v);
-                    }
-                    var
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            {
+                let __values = $args.collect_values($arg, |v| {
+                    $crate::__convert! { stringify!($var), v; $(#[$($attr)*])* }
+                });
+                let $var = match __values {
+                    Ok(var) => var,
+                    Err(e) => $crate::__fail!($args, e),
+                };
+                if let Err(e) = $args.take_errors() {
+                    break Err(e);
                 }
-                Err(opt) => break Err(Error::Unexpected(opt)),
-            };
 
-            break Ok($body);
+                break Ok($body);
+            }
         }
 
         $crate::__cmd! { $args, $arg; $($($rest)*)* }
     };
     ($args:ident, $arg:ident; $(#[$($attr:tt)*])* $cmd:literal $(| $cmd2:literal)* $({ $($params:tt)* })? $(=> $body:expr)? $(, $($rest:tt)*)?) => {
-        $(#[$($attr)*])*
-        if let Some($cmd $(| $cmd2)*) = $arg.as_deref() {
-            $crate::__help! { $args, $(#[$($attr)*])* $cmd; $($($params)*)* }
-            $crate::__init! { $($($params)*)* }
-
-            match $crate::__loop! { $args; $($($params)*)* } {
-                Ok(v) => {
-                    $(let () = v; break Ok($body);)*
-                    break Ok(v);
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            if let Some($cmd $(| $cmd2)*) = $arg.as_deref() {
+                $args.enter($cmd);
+                $crate::__init! { $($($params)*)* }
+
+                match $crate::__loop! { $args; $($($params)*)* } {
+                    Ok(v) => break Ok($crate::__body! { v; $($body)? }),
+                    Err(e) => break Err(e),
                 }
-                Err(e) => break Err(e),
             }
         }
 
         $crate::__cmd! { $args, $arg; $($($rest)*)* }
     };
     ($args:ident, $arg:ident; $(#[$($attr:tt)*])* _ => $body:expr $(, $($rest:tt)*)?) => {
-        $(#[$($attr)*])*
-        match $arg {
-            Some(v) => break Err(Error::Unexpected(v)),
-            None => break Ok($body),
+        $crate::__attrs! { [] $(#[$($attr)*])*
+            match $arg {
+                Some(v) => $crate::__fail!($args, Error::Unexpected(v)),
+                None => match $args.take_errors() {
+                    Ok(()) => break Ok($body),
+                    Err(e) => break Err(e),
+                },
+            }
         }
 
         $crate::__cmd! { $args, $arg; $($($rest)*)* }
     };
     ($args:ident, $arg:ident;) => {
-        break Err($arg.map_or(Error::MissingCommand, Error::UnknownCommand));
+        $crate::__fail!($args, $arg.map_or(Error::MissingCommand, Error::UnknownCommand));
+    };
+}
+
+// A subcommand's body, or the result of its nested definitions if it has none. Bodies can only
+// follow nested definitions that return `()`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __body {
+    ($v:ident; $body:expr) => {{
+        let () = $v;
+        $body
+    }};
+    ($v:ident;) => {
+        $v
     };
 }
 
@@ -179,88 +413,378 @@ macro_rules! __cmd {
 #[macro_export]
 macro_rules! __help {
     ($args:ident; $($rest:tt)*) => {
-        $args.context.name = ::std::env!("CARGO_PKG_NAME").into();
-        $crate::__help_options! { [], [], $args; $($rest)* }
-    };
-    ($args:ident, $(#[$($attr:tt)*])* $str:literal; $($rest:tt)*) => {
-        $args.context.name += concat!(" ", $str);
-        $args.context.description = $crate::__doc! { $(#[$($attr)*])* };
-        $crate::__help_options! { [], [], $args; $($rest)* }
+        const __COMMAND: $crate::__priv::Command<'static> = $crate::__command! { [] []; $($rest)* };
+        $args.enter_root(&__COMMAND, ::std::env!("CARGO_PKG_NAME"));
     };
 }
 
 #[doc(hidden)]
 #[macro_export]
-macro_rules! __help_options {
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
-            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, " <value>", $crate::__doc! { $(#[$($attr)*])* })],
+macro_rules! __command {
+    ([$($names:expr),*] [$($attrs:tt)*]; #![$($attr:tt)*] $($rest:tt)*) => {
+        $crate::__command! { [$($names),*] [$($attrs)* #[$($attr)*]]; $($rest)* }
+    };
+    ([$($names:expr),*] [$($attrs:tt)*]; $($rest:tt)*) => {
+        $crate::__command! {
+            {
+                names: &[$($names),*],
+                description: $crate::__doc! { $($attrs)* },
+                examples: $crate::__examples! { [] $($attrs)* },
+                after_help: $crate::__after_help! { $($attrs)* },
+                hidden: $crate::__hidden! { $($attrs)* },
+            }
+            [], [], [], [];
+            $($rest)*
+        }
+    };
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident? $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Value, $crate::__value_name! { $(#[$($attr)*])* }, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
             [$($usages),*],
-            $args; $($($rest)*)*
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
-            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, "", $crate::__doc! { $(#[$($attr)*])* })],
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident?($implicit:literal) $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Optional($implicit), $crate::__value_name! { $(#[$($attr)*])* }, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
             [$($usages),*],
-            $args; $($($rest)*)*
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
-            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, "", $crate::__doc! { $(#[$($attr)*])* })],
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident{} $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Map, $crate::__value_name! { $(#[$($attr)*])* }, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
             [$($usages),*],
-            $args; $($($rest)*)*
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* $bind:ident => $body:expr $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident?[$n:literal] $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Values($n), $crate::__value_name! { $(#[$($attr)*])* }, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($usages),*],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
+        }
+    };
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident+ $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Count, "", $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($usages),*],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
+        }
+    };
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident* $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::List($crate::__delimiter! { $(#[$($attr)*])* }), $crate::__value_name! { $(#[$($attr)*])* }, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($usages),*],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
+        }
+    };
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $opt:ident $(= $($v:ident)|+)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
+            [$($opts,)* ($crate::__help_opt! { $opt $(= $($v)|*)* }, $crate::__priv::Kind::Switch, "", $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($usages),*],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
+        }
+    };
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $bind:ident => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
             [$($opts),*],
-            [$($usages,)* (concat!("[", stringify!($bind), "]"), $crate::__doc! { $(#[$($attr)*])* })],
-            $args; $($($rest)*)*
+            [$($usages,)* ($crate::__priv::Usage::Value(stringify!($bind)), $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* ..$var:ident => $body:expr $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* ..$var:ident => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
             [$($opts),*],
-            [$($usages,)* (concat!("[", stringify!($var), "].."), $crate::__doc! { $(#[$($attr)*])* })],
-            $args; $($($rest)*)*
+            [$($usages,)* ($crate::__priv::Usage::Values(stringify!($var)), $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* $str:literal $(| $str2:literal)* $({ $($params:tt)* })? $(=> $body:expr)? $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* $str:literal $(| $str2:literal)* $({ $($params:tt)* })? $(=> $body:expr)? $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
             [$($opts),*],
-            [$($usages,)* (concat!($str, $(", ", $str2)*), $crate::__doc! { $(#[$($attr)*])* })],
-            $args; $($($rest)*)*
+            [$($usages,)* ($crate::__priv::Usage::Command(&[$str $(, $str2)*]), $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($cmds,)* $crate::__command! { [$str $(, $str2)*] [$(#[$($attr)*])*]; $($($params)*)* }],
+            [($crate::__priv::Usage::Help, "Display help for a command", false)];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident; $(#[$($attr:tt)*])* _ => $body:expr $(, $($rest:tt)*)?) => {
-        $crate::__help_options! {
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*]; $(#[$($attr:tt)*])* _ => $body:expr $(, $($rest:tt)*)?) => {
+        $crate::__command! {
+            { $($fields)* }
             [$($opts),*],
-            [$($usages,)* ("", $crate::__doc! { $(#[$($attr)*])* })],
-            $args; $($($rest)*)*
+            [$($usages,)* ($crate::__priv::Usage::Empty, $crate::__doc! { $(#[$($attr)*])* }, $crate::__hidden! { $(#[$($attr)*])* })],
+            [$($cmds),*],
+            [$($help)*];
+            $($($rest)*)*
         }
     };
-    ([$($opts:expr),*], [$($usages:expr),*], $args:ident;) => {
-        $args.context.options = &[$($opts),*];
-        $args.context.usages = &[$($usages),*];
+    ({ $($fields:tt)* } [$($opts:expr),*], [$($usages:expr),*], [$($cmds:expr),*], [$($help:tt)*];) => {
+        $crate::__priv::Command {
+            $($fields)*
+            options: &[$($opts),*],
+            usages: &[$($usages,)* $($help)*],
+            commands: &[$($cmds),*],
+        }
     };
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __doc {
-    (#[doc = $doc:literal] $(#[$($attr:tt)*])*) => {
-        $doc
+    (@[$($doc:literal)*] #[doc = $line:literal] $(#[$($attr:tt)*])*) => {
+        $crate::__doc! { @[$($doc)* $line] $(#[$($attr)*])* }
+    };
+    (@[$($doc:literal)*] #[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__doc! { @[$($doc)*] $(#[$($attr)*])* }
+    };
+    (@[$($doc:literal)*]) => {
+        concat!($($doc, "\n"),*)
+    };
+    ($(#[$($attr:tt)*])*) => {
+        $crate::__doc! { @[] $(#[$($attr)*])* }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __examples {
+    ([$($ex:expr),*] #[example($cmd:literal $(, $doc:literal)?)] $($rest:tt)*) => {
+        $crate::__examples! { [$($ex,)* ($cmd, concat!("" $(, $doc)?))] $($rest)* }
+    };
+    ([$($ex:expr),*] #[$($_:tt)*] $($rest:tt)*) => {
+        $crate::__examples! { [$($ex),*] $($rest)* }
+    };
+    ([$($ex:expr),*]) => {
+        &[$($ex),*]
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __after_help {
+    (#[after_help = $text:literal] $(#[$($attr:tt)*])*) => {
+        $text
     };
     (#[$($_:tt)*] $(#[$($attr:tt)*])*) => {
-        $crate::__doc! { $(#[$($attr)*])* }
+        $crate::__after_help! { $(#[$($attr)*])* }
     };
     () => {
         ""
     };
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __delimiter {
+    (#[delimiter = $delimiter:literal] $(#[$($attr:tt)*])*) => {
+        $delimiter
+    };
+    (#[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__delimiter! { $(#[$($attr)*])* }
+    };
+    () => {
+        ','
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __duplicates {
+    (#[duplicates = "first"] $(#[$($attr:tt)*])*) => {
+        $crate::__priv::Duplicates::First
+    };
+    (#[duplicates = "last"] $(#[$($attr:tt)*])*) => {
+        $crate::__priv::Duplicates::Last
+    };
+    (#[duplicates = "error"] $(#[$($attr:tt)*])*) => {
+        $crate::__priv::Duplicates::Error
+    };
+    (#[duplicates = $other:literal] $(#[$($attr:tt)*])*) => {
+        compile_error!(concat!(
+            "unknown duplicates policy ",
+            stringify!($other),
+            ", expected \"first\", \"last\" or \"error\"",
+        ))
+    };
+    (#[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__duplicates! { $(#[$($attr)*])* }
+    };
+    () => {
+        $crate::__priv::Duplicates::Last
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __value_name {
+    (#[value_name = $name:literal] $(#[$($attr:tt)*])*) => {
+        $name
+    };
+    (#[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__value_name! { $(#[$($attr)*])* }
+    };
+    () => {
+        ""
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __hidden {
+    (#[hidden] $(#[$($attr:tt)*])*) => {
+        true
+    };
+    (#[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__hidden! { $(#[$($attr)*])* }
+    };
+    () => {
+        false
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __deprecated {
+    ($args:ident, $arg:ident; #[deprecated_opt $((note = $note:literal))?] $(#[$($attr:tt)*])*) => {
+        let __opt = $arg.as_ref().map_or_else(String::new, ToString::to_string);
+        $args.deprecated(&__opt, concat!("" $(, $note)?));
+    };
+    ($args:ident, $arg:ident; #[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__deprecated! { $args, $arg; $(#[$($attr)*])* }
+    };
+    ($args:ident, $arg:ident;) => {};
+}
+
+/// Stop parsing with an error, or keep going if collecting errors, see `Args::collect_errors`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __fail {
+    ($args:ident, $error:expr) => {{
+        let __error = $error;
+        match $args.recover(__error) {
+            Ok(()) => continue,
+            Err(e) => break Err(e),
+        }
+    }};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __validate {
+    ($option:expr, $value:expr; #[validate($f:expr)] $(#[$($attr:tt)*])*) => {
+        match $crate::__priv::validate(&$value, $f) {
+            Ok(()) => $crate::__validate! { $option, $value; $(#[$($attr)*])* },
+            Err(message) => Err(Error::Invalid {
+                option: $option.to_string(),
+                value: $value.to_string(),
+                message,
+            }),
+        }
+    };
+    ($option:expr, $value:expr; #[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__validate! { $option, $value; $(#[$($attr)*])* }
+    };
+    ($option:expr, $value:expr;) => {
+        Ok::<(), Error>(())
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __parse {
+    ($option:expr, $value:expr; #[parse($f:expr)] $(#[$($attr:tt)*])*) => {
+        $crate::__priv::parse(&$value, $f).map_err(|message| Error::Invalid {
+            option: $option.to_string(),
+            value: $value.to_string(),
+            message,
+        })
+    };
+    ($option:expr, $value:expr; #[$($_:tt)*] $(#[$($attr:tt)*])*) => {
+        $crate::__parse! { $option, $value; $(#[$($attr)*])* }
+    };
+    ($option:expr, $value:expr;) => {
+        Ok::<_, Error>($value)
+    };
+}
+
+/// Validate and then parse a value, giving the option's final value.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __convert {
+    ($option:expr, $value:expr; $(#[$($attr:tt)*])*) => {
+        $crate::__validate! { $option, $value; $(#[$($attr)*])* }
+            .and_then(|()| $crate::__parse! { $option, $value; $(#[$($attr)*])* })
+    };
+}
+
+/// Remove sylveon's own attributes before emitting a statement.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __attrs {
+    ([$($kept:tt)*] #[example $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[after_help $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[hidden] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[deprecated_opt $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[delimiter $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[value_name $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[duplicates $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[validate $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[parse $($_:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)*] $($rest)* }
+    };
+    ([$($kept:tt)*] #[$($attr:tt)*] $($rest:tt)*) => {
+        $crate::__attrs! { [$($kept)* #[$($attr)*]] $($rest)* }
+    };
+    ([$($kept:tt)*] $($stmt:tt)*) => {
+        $($kept)* $($stmt)*
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! __pat {
@@ -0,0 +1,338 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+use std::{env, fs, io};
+
+use crate::parser::{Command, Entries, Kind};
+use crate::{shell, Args, Error, Opt};
+
+impl Args {
+    /// Read options from a config file, if it exists. The path can be overridden with
+    /// `--config <path>`, in which case the file must exist, and this option is listed in the
+    /// root command's help. If the root command declares its own `config?` option, that option
+    /// gives the path instead, and still gets its value.
+    ///
+    /// The file uses a small subset of TOML, which also covers most INI files:
+    ///
+    /// ```toml
+    /// # options for the root command
+    /// verbose = 2
+    /// ignore = "target"
+    ///
+    /// # options for the `remote add` subcommand
+    /// [remote.add]
+    /// force = true
+    /// ```
+    ///
+    /// Keys are the same as the option names. Options given on the command line or with
+    /// [`Args::env`] take precedence, and the file's value for them is ignored, so e.g. counts and
+    /// lists aren't added to. Switches have no way to be turned off, so one set to `true` here
+    /// can't be unset from the command line; prefer a value like `color = "never"` for settings
+    /// users may want to turn off. A leading `~/` is replaced with the home directory. Errors caused by these
+    /// options mention the file, like those from [`Args::env`], and are reported after parsing so
+    /// `--help` still works.
+    ///
+    /// # Panics
+    ///
+    /// When parsing starts, if the root command defines a `config` option that doesn't take a
+    /// value.
+    pub fn config(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config = Some(path.into());
+        self
+    }
+
+    /// Read default arguments from an environment variable, like `LESS` or `GREP_OPTIONS`.
+    ///
+    /// The variable is split into words like [`Args::from_shell_str`], and the words are given
+    /// after the config file's options but before the command-line arguments. Errors caused by
+    /// these arguments mention the variable, e.g. `in $TOOL_OPTS: unexpected argument: --x`.
+    pub fn env(mut self, var: impl Into<String>) -> Self {
+        self.env = Some(var.into());
+        self
+    }
+
+    /// Read the environment variable, if any, and insert its arguments. Called when parsing
+    /// starts, before [`Args::load_config`].
+    pub(crate) fn load_env(&mut self) -> Result<(), Error> {
+        let Some(var) = &self.env else {
+            return Ok(());
+        };
+        let Some(value) = env::var_os(var) else {
+            return Ok(());
+        };
+
+        let origin = format!("${var}");
+        let args = shell::split(&value.to_string_lossy())
+            .map_err(|e| Error::Config(format!("in {origin}: {e}")))?;
+
+        self.inject(args, origin);
+
+        Ok(())
+    }
+
+    /// Read the config file, if any, and queue its options. Called when parsing starts.
+    pub(crate) fn load_config(&mut self) -> Result<(), Error> {
+        let Some(default) = self.config.clone() else {
+            return Ok(());
+        };
+
+        let declared = self.context.root.long_kind("config");
+        let given = match declared {
+            None => self.take_option("config"),
+            Some(Kind::Value) => self.find_option("config").map(|(_, path)| path),
+            Some(_) => panic!("the root command's `config` option must take a value"),
+        };
+        let (path, explicit) = match given {
+            Some(path) => (PathBuf::from(path), true),
+            None => (expand_home(&default), false),
+        };
+        let error = |msg: String| Error::Config(format!("{}: {msg}", path.display()));
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if !explicit && e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(error(e.to_string())),
+        };
+
+        let mut sections: Vec<(&'static Command<'static>, Entries)> = Vec::new();
+        let mut section = (self.context.root, Vec::new());
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let line_error = |msg: String| error(format!("line {}: {msg}", i + 1));
+
+            if line.is_empty() || line.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let Some(header) = strip_comment(header).trim_end().strip_suffix(']') else {
+                    return Err(line_error("expected ']'".into()));
+                };
+
+                let mut cmd = self.context.root;
+                for name in header.split('.').map(str::trim) {
+                    cmd = cmd
+                        .find(name)
+                        .ok_or_else(|| line_error(format!("unknown command '{header}'")))?;
+                }
+
+                sections.push(std::mem::replace(&mut section, (cmd, Vec::new())));
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(line_error("expected 'key = value'".into()));
+            };
+
+            let key = key.trim();
+            let value = Value::parse(value.trim()).map_err(line_error)?;
+            let mut options = section.0.options.iter().enumerate();
+            let option = options.find_map(|(i, (opts, kind, ..))| {
+                let opt = opts.iter().find(|opt| match opt {
+                    Opt::Long(name) => *name == key,
+                    Opt::Short(c) => key.chars().eq([*c]),
+                    Opt::Value(_) => false,
+                })?;
+                let syntax = self.context.syntax;
+                Some((i, syntax.format(opt), syntax.attach(opt), *kind))
+            });
+
+            let Some((i, opt, attached, kind)) = option else {
+                return Err(line_error(format!("unknown option '{key}'")));
+            };
+            if declared.is_some() && key == "config" && std::ptr::eq(section.0, self.context.root) {
+                return Err(line_error("the config file can't set its own path".into()));
+            }
+
+            let mut args = Vec::new();
+            value
+                .into_args(&opt, &attached, kind, &mut args)
+                .map_err(|msg| line_error(format!("{msg} for '{key}'")))?;
+            section.1.push((i, args));
+        }
+
+        sections.push(section);
+
+        let origin = path.display().to_string();
+        let (_, root) = sections.remove(0);
+        for (cmd, entries) in sections {
+            match self
+                .sections
+                .iter_mut()
+                .find(|(c, ..)| std::ptr::eq(*c, cmd))
+            {
+                Some((.., queued)) => queued.extend(entries),
+                None => self.sections.push((cmd, origin.clone(), entries)),
+            }
+        }
+        self.inject_config(root, origin);
+
+        Ok(())
+    }
+}
+
+/// A value in the config file.
+enum Value {
+    Bool(bool),
+    Int(u64),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn parse(text: &str) -> Result<Self, String> {
+        let (value, rest) = Self::parse_next(text, false)?;
+
+        match strip_comment(rest).trim() {
+            "" => Ok(value),
+            rest => Err(format!("unexpected '{rest}'")),
+        }
+    }
+
+    /// Parse a value from the start of `text`, returning the rest.
+    fn parse_next(text: &str, in_list: bool) -> Result<(Self, &str), String> {
+        if let Some(mut rest) = text.strip_prefix('[') {
+            let mut items = Vec::new();
+
+            loop {
+                rest = rest.trim_start();
+                if let Some(rest) = rest.strip_prefix(']') {
+                    return Ok((Self::List(items), rest));
+                }
+
+                let (item, next) = Self::parse_next(rest, true)?;
+                items.push(item);
+
+                rest = next.trim_start();
+                rest = match rest.strip_prefix(',') {
+                    Some(rest) => rest,
+                    None if rest.starts_with(']') => rest,
+                    None => return Err("expected ',' or ']'".into()),
+                };
+            }
+        }
+
+        if let Some(rest) = text.strip_prefix('\'') {
+            let end = rest.find('\'').ok_or("unterminated string")?;
+            return Ok((Self::Str(rest[..end].to_owned()), &rest[end + 1..]));
+        }
+
+        if let Some(rest) = text.strip_prefix('"') {
+            let mut value = String::new();
+            let mut chars = rest.char_indices();
+
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => return Ok((Self::Str(value), &rest[i + 1..])),
+                    '\\' => match chars.next() {
+                        Some((_, 'n')) => value.push('\n'),
+                        Some((_, 't')) => value.push('\t'),
+                        Some((_, c @ ('"' | '\\'))) => value.push(c),
+                        _ => return Err("invalid escape in string".into()),
+                    },
+                    c => value.push(c),
+                }
+            }
+
+            return Err("unterminated string".into());
+        }
+
+        // bare values end at the next separator inside arrays, otherwise at a comment
+        let end = if in_list {
+            text.find([',', ']', '#', ';'])
+        } else {
+            text.find(['#', ';'])
+        };
+        let (word, rest) = text.split_at(end.unwrap_or(text.len()));
+        let word = word.trim();
+
+        let value = match word {
+            "true" => Self::Bool(true),
+            "false" => Self::Bool(false),
+            _ if word.is_empty() => return Err("expected a value".into()),
+            _ => match word.parse() {
+                Ok(n) => Self::Int(n),
+                Err(_) => Self::Str(word.to_owned()),
+            },
+        };
+
+        Ok((value, rest))
+    }
+
+    /// Convert this value to arguments for the option `opt`, or `attached` followed by the value.
+    fn into_args(
+        self,
+        opt: &str,
+        attached: &str,
+        kind: Kind,
+        args: &mut Vec<String>,
+    ) -> Result<(), String> {
+        match (kind, self) {
+            (Kind::Switch, Self::Bool(b)) | (Kind::Count, Self::Bool(b)) => {
+                args.extend(b.then(|| opt.to_owned()));
+            }
+            (Kind::Switch, _) => return Err("expected a boolean".into()),
+            (Kind::Optional(_), Self::Bool(b)) => {
+                args.extend(b.then(|| opt.to_owned()));
+            }
+            (Kind::Optional(_), Self::Int(n)) => args.push(format!("{attached}{n}")),
+            (Kind::Optional(_), Self::Str(s)) => args.push(format!("{attached}{s}")),
+            (Kind::Optional(_), Self::List(_)) => return Err("unexpected list".into()),
+            (Kind::Count, Self::Int(n)) => {
+                args.extend((0..n).map(|_| opt.to_owned()));
+            }
+            (Kind::Count, _) => return Err("expected a number".into()),
+            (Kind::Values(n), Self::List(items)) if items.len() == n => {
+                args.push(opt.to_owned());
+                for item in items {
+                    match item {
+                        Self::Bool(b) => args.push(b.to_string()),
+                        Self::Int(i) => args.push(i.to_string()),
+                        Self::Str(s) => args.push(s),
+                        Self::List(_) => return Err("unexpected list".into()),
+                    }
+                }
+            }
+            (Kind::Values(n), _) => return Err(format!("expected a list of {n} values")),
+            (Kind::Value | Kind::List(_) | Kind::Map, Self::List(items)) => {
+                for item in items {
+                    item.into_args(opt, attached, kind, args)?;
+                }
+            }
+            (Kind::Value | Kind::List(_) | Kind::Map, value) => {
+                let value = match value {
+                    Self::Bool(b) => b.to_string(),
+                    Self::Int(n) => n.to_string(),
+                    Self::Str(s) => s,
+                    Self::List(_) => unreachable!(),
+                };
+
+                args.push(opt.to_owned());
+                args.push(match kind {
+                    // each item is a single value, so escape any delimiters
+                    Kind::List(delimiter) => value
+                        .replace('\\', "\\\\")
+                        .replace(delimiter, &format!("\\{delimiter}")),
+                    _ => value,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    text.split(['#', ';']).next().unwrap_or_default()
+}
+
+fn expand_home(path: &std::path::Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_owned(),
+    }
+}
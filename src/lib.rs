@@ -9,9 +9,18 @@
 
 #[doc(hidden)]
 pub mod __priv;
+mod config;
 mod parser;
+mod reference;
+mod repl;
+mod shell;
+pub mod testing;
+pub mod value;
 
-pub use parser::{Args, Color, Opt, Style};
+pub use parser::{Args, Color, Opt, Span, Style, Syntax, Verbosity};
+pub use reference::Reference;
+pub use repl::{Completer, Repl};
+pub use shell::ShellError;
 
 /// Parse CLI arguments.
 ///
@@ -50,29 +59,42 @@ macro_rules! try_parse {
     ($args:ident; $($rest:tt)*) => {{
         use $crate::__priv::*;
 
+        $crate::__help! { $args; $($rest)* }
+
         $crate::__init! { $($rest)* }
         #[allow(unreachable_code, clippy::diverging_sub_expression)]
-        $crate::__loop! { $args, $($rest)* }
+        let result = $crate::__loop! { $args; $($rest)* };
+        result
     }};
     ($($rest:tt)*) => {{
-        use $crate::__priv::*;
-
         let mut __args = $crate::Args::new();
-
-        $crate::__init! { $($rest)* }
-        #[allow(unreachable_code, clippy::diverging_sub_expression)]
-        $crate::__loop! { __args; $($rest)* }
+        $crate::try_parse! { __args; $($rest)* }
     }};
 }
 
 /// [`parse`] exit condition. This may occur with invalid arguments, or if `--help` is given,
 /// [`Error::Help`].
+///
+/// Where an error caused by an argument occurred is kept by the parser, see
+/// [`Args::error_span`].
 #[derive(Debug)]
 pub enum Error {
-    /// The help message should be displayed
-    Help,
+    /// The help message should be displayed, with `-h` or `--help`
+    Help(Verbosity),
+    /// The whole command tree should be written as JSON, with `--help-json`, see
+    /// [`Args::write_json`]
+    HelpJson,
     /// An argument was missing a flag
     MissingValue,
+    /// An option taking several values was missing some of them
+    MissingValues {
+        /// The option, as written
+        option: String,
+        /// How many values the option takes
+        expected: usize,
+        /// How many values were given
+        found: usize,
+    },
     /// Missing subcommand
     MissingCommand,
     /// Unexpected argument
@@ -81,51 +103,148 @@ pub enum Error {
     UnknownCommand(String),
     /// Missing required argument
     Required(String),
+    /// Invalid config file or environment variable
+    Config(String),
+    /// An option's value was invalid
+    Invalid {
+        /// The option, as written
+        option: String,
+        /// The value given
+        value: String,
+        /// Why the value is invalid
+        message: String,
+    },
+    /// Abbreviated option or subcommand, with all of its matches
+    Ambiguous(String, Vec<String>),
+    /// Several errors, when collecting them with [`Args::collect_errors`]
+    Multiple(Vec<Error>),
 }
 
 impl Error {
-    /// Exit the program with this condition. [`Error::Help`] will display the help message and
-    /// exit with code 0, otherwise display an error message and exit with code 1.
+    /// Exit the program with this condition. [`Error::Help`] and [`Error::HelpJson`] will display
+    /// the help message and exit with code 0, otherwise display an error message and exit with
+    /// code 1.
     pub fn terminate(self, args: parser::Args) -> ! {
-        let mut w = std::io::stderr().lock();
-
         match self {
-            Self::Help => {
-                args.style
-                    .format_help(&args.context, &mut std::io::stdout().lock())
-                    .unwrap();
-
+            Self::Help(_) | Self::HelpJson => {
+                self.render(&args, &mut std::io::stdout().lock()).unwrap();
                 std::process::exit(0);
             }
-            Self::MissingValue => {
-                let opt = args.peek_back().unwrap();
-                args.style
-                    .format_error(&format!("option '{opt}' requires a value"), &mut w)
-                    .unwrap();
+            _ => {
+                self.render(&args, &mut std::io::stderr().lock()).unwrap();
+                std::process::exit(1);
             }
-            Self::MissingCommand => {
-                let name = args.context.name;
-                args.style
-                    .format_error(&format!("missing subcommand for {name}"), &mut w)
-                    .unwrap();
+        }
+    }
+
+    /// Write the help message or error message for this condition, like [`Error::terminate`] but
+    /// without exiting. Errors returned by parsing also show where they occurred, see
+    /// [`Args::error_span`].
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn render(&self, args: &parser::Args, f: &mut impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            Self::Help(verbosity) => args.render_help(*verbosity, f),
+            Self::HelpJson => args.write_json(f),
+            Self::Multiple(errors) => {
+                let spans = args.error_spans().iter().map(Option::as_ref);
+                let spans = spans.chain(std::iter::repeat(None));
+                (errors.iter().zip(spans)).try_for_each(|(e, span)| e.render_at(args, span, f))
             }
-            Self::Unexpected(v) => {
-                args.style
-                    .format_error(&format!("unexpected argument: {v}"), &mut w)
-                    .unwrap();
+            _ => self.render_at(args, args.error_span(), f),
+        }
+    }
+
+    /// Write the error message, and where the error occurred if known.
+    fn render_at(
+        &self,
+        args: &parser::Args,
+        span: Option<&Span>,
+        f: &mut impl std::io::Write,
+    ) -> std::io::Result<()> {
+        args.style.format_error(&self.message(args, span), f)?;
+        let Some(span) = span else {
+            return Ok(());
+        };
+
+        let words = args.words(span.origin.as_deref());
+        match span.origin {
+            // show the program name too, without counting it in the span, unless there's none as
+            // in a REPL
+            None if !args.program().is_empty() => {
+                let words = [&[args.program()], &words[..]].concat();
+                let span = Span {
+                    index: span.index + 1,
+                    ..span.clone()
+                };
+                args.style.format_span(&words, &span, f)
             }
-            Self::UnknownCommand(v) => {
-                args.style
-                    .format_error(&format!("unknown command: {v}"), &mut w)
-                    .unwrap();
+            _ => args.style.format_span(&words, span, f),
+        }
+    }
+
+    /// Get the error message, using the location of the argument that caused it, if known.
+    fn message(&self, args: &parser::Args, span: Option<&Span>) -> String {
+        let mut error = match self {
+            Self::Help(_) | Self::HelpJson | Self::Multiple(_) => unreachable!(),
+            Self::MissingValue => {
+                let opt = match span {
+                    Some(span) => {
+                        let words = args.words(span.origin.as_deref());
+                        let word = words.get(span.index).copied().unwrap_or_default();
+                        let opt = word.get(span.range.clone()).unwrap_or_default();
+                        // short options in a cluster are only a single character
+                        match (span.range.start, opt.chars().next()) {
+                            (1.., Some(c)) => args.context.syntax.format(&Opt::Short(c)),
+                            _ => opt.to_owned(),
+                        }
+                    }
+                    None => args.peek_back().unwrap_or_default().to_owned(),
+                };
+                format!("option '{opt}' requires a value")
             }
-            Self::Required(v) => {
-                args.style
-                    .format_error(&format!("missing required argument: {v}"), &mut w)
-                    .unwrap();
+            Self::MissingValues {
+                option,
+                expected,
+                found,
+            } => format!("option '{option}' requires {expected} values, found {found}"),
+            Self::MissingCommand => format!("missing subcommand for {}", args.context.name),
+            Self::Unexpected(v) if span.is_some() => format!("unexpected argument: {v}"),
+            Self::Unexpected(v) => format!("unexpected argument: {}", args.written(v)),
+            Self::UnknownCommand(v) => format!("unknown command: {v}"),
+            Self::Required(v) => format!("missing required argument: {v}"),
+            Self::Config(v) => v.clone(),
+            Self::Invalid {
+                option,
+                value,
+                message,
+            } => format!("invalid value '{value}' for '{option}': {message}"),
+            Self::Ambiguous(v, candidates) => {
+                format!(
+                    "ambiguous argument '{v}', could be {}",
+                    candidates.join(", ")
+                )
             }
+        };
+
+        // mention where arguments came from, if not the command line
+        if let (
+            Self::MissingValue
+            | Self::MissingValues { .. }
+            | Self::Unexpected(_)
+            | Self::UnknownCommand(_)
+            | Self::Invalid { .. }
+            | Self::Ambiguous(..),
+            Some(origin),
+        ) = (
+            self,
+            span.map_or(args.origin(), |span| span.origin.as_deref()),
+        ) {
+            error = format!("in {origin}: {error}");
         }
 
-        std::process::exit(1)
+        error
     }
 }
@@ -3,8 +3,11 @@
 //
 // SPDX-License-Identifier: Apache-2.0
 
-use std::borrow::Cow;
 use std::io;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use crate::Error;
 
 /// A command line option. This is used by [`parse`](crate::parse).
 #[derive(Debug)]
@@ -27,49 +30,421 @@ impl std::fmt::Display for Opt<'_> {
     }
 }
 
+#[derive(Clone, Copy)]
 enum State {
     Read(usize),
     Eoi(usize),
     Short(usize, usize),
+    /// An option with its value in the same argument, starting at the given byte
+    Attached(usize, usize),
     Empty,
 }
 
-#[derive(Default)]
-pub struct Context<'a> {
-    pub name: Cow<'a, str>,
+/// An option read from the arguments, see [`Args::next_opt`].
+enum Token {
+    Short(char),
+    Long(usize, Range<usize>),
+    Value(usize),
+    /// A long option from the definition, e.g. after expanding an abbreviation, and where it was
+    /// written if it wasn't expanded
+    Name(&'static str, Option<(usize, Range<usize>)>),
+}
+
+/// How options are written on the command line, see [`Args::syntax`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// `-s`, `--long` and `--long=value`, where short options can be combined as `-abc` and `--`
+    /// ends the options
+    #[default]
+    Unix,
+    /// `-long` and `-long=value` like in Go or X11, also allowing `--long`. Words that aren't a
+    /// long option of the current command are read as short options, e.g. `-abc`
+    SingleDash,
+    /// `/s`, `/long` and `/long:value`, ignoring case, where `/?` shows help
+    Dos,
+    /// `key=value` like in `dd`, where `key` is an option that takes a value. Other arguments are
+    /// read as with [`Syntax::Unix`]
+    KeyValue,
+}
+
+impl Syntax {
+    /// Format an option as it would be written.
+    pub(crate) fn format(self, opt: &Opt) -> String {
+        match (self, opt) {
+            (Self::Unix | Self::KeyValue, opt) | (_, opt @ Opt::Value(_)) => opt.to_string(),
+            (Self::SingleDash, Opt::Short(c)) => format!("-{c}"),
+            (Self::SingleDash, Opt::Long(name)) => format!("-{name}"),
+            (Self::Dos, Opt::Short(c)) => format!("/{c}"),
+            (Self::Dos, Opt::Long(name)) => format!("/{name}"),
+        }
+    }
+
+    /// Format all names of an option, followed by its placeholder.
+    pub(crate) fn format_names(self, opts: &[Opt], kind: Kind, value_name: &str) -> String {
+        let placeholder = kind.placeholder(value_name);
+        if self == Self::KeyValue && kind.attaches() {
+            let names = opts.iter().map(|opt| match opt {
+                Opt::Long(name) => name.to_string(),
+                opt => opt.to_string(),
+            });
+            // optional values must be given as `key=value` too, so they aren't shown as optional
+            let placeholder = match kind {
+                Kind::Optional(_) => placeholder.replacen("[=", "=", 1).replacen(']', "", 1),
+                _ => placeholder.replacen(' ', "=", 1),
+            };
+            return names.collect::<Vec<_>>().join(", ") + &placeholder;
+        }
+
+        let names = opts.iter().map(|opt| self.format(opt)).collect::<Vec<_>>();
+        let placeholder = match self {
+            Self::Unix | Self::SingleDash | Self::KeyValue => placeholder,
+            Self::Dos => placeholder.replacen([' ', '='], ":", 1),
+        };
+
+        names.join(", ") + &placeholder
+    }
+
+    /// Format an option with the start of a value in the same argument, e.g. `--name=` or `-n`.
+    pub(crate) fn attach(self, opt: &Opt) -> String {
+        match (self, opt) {
+            (Self::Dos, _) | (_, Opt::Long(_)) => {
+                format!("{}{}", self.format(opt), self.separator())
+            }
+            (_, opt) => self.format(opt),
+        }
+    }
+
+    /// The separator between an option and its value in the same argument.
+    fn separator(self) -> char {
+        match self {
+            Self::Unix | Self::SingleDash | Self::KeyValue => '=',
+            Self::Dos => ':',
+        }
+    }
+}
+
+/// A command's definition, built by [`parse`](crate::parse) for the whole command tree.
+#[doc(hidden)]
+pub struct Command<'a> {
+    pub names: &'a [&'a str],
     pub description: &'a str,
-    pub usages: &'a [(&'a str, &'a str)],
-    pub options: &'a [(&'a [Opt<'a>], &'a str, &'a str)],
-    pub commands: &'a [(&'a str, &'a str)],
+    pub usages: &'a [(Usage<'a>, &'a str, bool)],
+    pub options: &'a [(&'a [Opt<'a>], Kind, &'a str, &'a str, bool)],
+    pub commands: &'a [Command<'a>],
+    pub examples: &'a [(&'a str, &'a str)],
+    pub after_help: &'a str,
+    pub hidden: bool,
 }
 
+impl<'a> Command<'a> {
+    /// Find a subcommand by any of its names.
+    pub(crate) fn find(&self, name: &str) -> Option<&'a Command<'a>> {
+        self.commands.iter().find(|cmd| cmd.names.contains(&name))
+    }
+
+    /// Check if this command has a long option, including `help` and `help-json`.
+    pub(crate) fn has_long(&self, name: &str) -> bool {
+        matches!(name, "help" | "help-json") || self.long_kind(name).is_some()
+    }
+
+    /// Check if this command has a short option, including `h`.
+    pub(crate) fn has_short(&self, c: char) -> bool {
+        c == 'h'
+            || (self.options.iter())
+                .flat_map(|(opts, ..)| opts.iter())
+                .any(|opt| matches!(opt, Opt::Short(short) if *short == c))
+    }
+
+    /// Check if this command has the `help <command>` subcommand, which every command with
+    /// subcommands has.
+    pub(crate) fn has_help_command(&self) -> bool {
+        !self.commands.is_empty()
+    }
+
+    /// Check if this command takes positional arguments.
+    pub(crate) fn has_positionals(&self) -> bool {
+        (self.usages.iter()).any(|(usage, ..)| matches!(usage, Usage::Value(_) | Usage::Values(_)))
+    }
+
+    /// Find a long option ignoring case, including `help` and `help-json`, and get its name as defined.
+    pub(crate) fn find_long_ignore_case(&self, name: &str) -> Option<&'a str> {
+        let longs = (self.options.iter())
+            .flat_map(|(opts, ..)| opts.iter())
+            .filter_map(|opt| match opt {
+                Opt::Long(long) => Some(*long),
+                _ => None,
+            })
+            .chain(["help", "help-json"]);
+
+        let mut found = None;
+        for long in longs {
+            if long == name {
+                return Some(long);
+            }
+            if found.is_none() && eq_ignore_case(long, name) {
+                found = Some(long);
+            }
+        }
+        found
+    }
+
+    /// Find a short option ignoring case, including `h`, and get its name as defined.
+    pub(crate) fn find_short_ignore_case(&self, c: char) -> Option<char> {
+        let shorts = (self.options.iter())
+            .flat_map(|(opts, ..)| opts.iter())
+            .filter_map(|opt| match opt {
+                Opt::Short(short) => Some(*short),
+                _ => None,
+            })
+            .chain(['h']);
+
+        let mut found = None;
+        for short in shorts {
+            if short == c {
+                return Some(short);
+            }
+            if found.is_none() && short.to_lowercase().eq(c.to_lowercase()) {
+                found = Some(short);
+            }
+        }
+        found
+    }
+
+    /// Get the kind of a long option.
+    pub(crate) fn long_kind(&self, name: &str) -> Option<Kind> {
+        let mut options = self.options.iter();
+        options
+            .find(|(opts, ..)| {
+                opts.iter()
+                    .any(|opt| matches!(opt, Opt::Long(l) if *l == name))
+            })
+            .map(|(_, kind, ..)| *kind)
+    }
+}
+
+/// Compare two names ignoring case.
+fn eq_ignore_case(a: &str, b: &str) -> bool {
+    let lower = |s: &str| s.chars().flat_map(char::to_lowercase).collect::<String>();
+    lower(a) == lower(b)
+}
+
+impl Command<'static> {
+    const EMPTY: Self = Self {
+        names: &[],
+        description: "",
+        usages: &[],
+        options: &[],
+        commands: &[],
+        examples: &[],
+        after_help: "",
+        hidden: false,
+    };
+}
+
+/// What an option does with its values.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Switch,
+    Count,
+    Value,
+    /// Values split on a delimiter
+    List(char),
+    /// A fixed number of values
+    Values(usize),
+    /// A value only if attached, e.g. `--color=always`, or an implicit value
+    Optional(&'static str),
+    /// `key=value` entries
+    Map,
+}
+
+/// What to do when a map option is given the same key twice.
+#[doc(hidden)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplicates {
+    First,
+    Last,
+    Error,
+}
+
+impl Kind {
+    /// Get the placeholder for this option's value, e.g. ` <value>`.
+    ///
+    /// With several values, `name` can also give each one a name, e.g. `width height`.
+    pub(crate) fn placeholder(self, name: &str) -> String {
+        let name = self.value_name(name);
+        match self {
+            Self::Switch | Self::Count => String::new(),
+            Self::Value | Self::Map => format!(" <{name}>"),
+            Self::List(delimiter) => format!(" <{name}{delimiter}...>"),
+            Self::Optional(_) => format!("[=<{name}>]"),
+            Self::Values(n) => match name.split_whitespace().collect::<Vec<_>>() {
+                names if names.len() == n => names.iter().map(|n| format!(" <{n}>")).collect(),
+                _ => format!(" <{name}>").repeat(n),
+            },
+        }
+    }
+
+    /// Get the name of this option's value, or a default if `name` is empty.
+    pub(crate) fn value_name(self, name: &str) -> &str {
+        match (self, name) {
+            (Self::Map, "") => "key=value",
+            (_, "") => "value",
+            (_, name) => name,
+        }
+    }
+
+    /// Get how many arguments this option reads after it.
+    pub(crate) fn arity(self) -> usize {
+        match self {
+            Self::Switch | Self::Count | Self::Optional(_) => 0,
+            Self::Value | Self::List(_) | Self::Map => 1,
+            Self::Values(n) => n,
+        }
+    }
+
+    pub(crate) fn takes_value(self) -> bool {
+        self.arity() > 0
+    }
+
+    /// Check if this option can have a value in the same argument, e.g. `--name=value`.
+    pub(crate) fn attaches(self) -> bool {
+        self.takes_value() || matches!(self, Self::Optional(_))
+    }
+}
+
+/// A line in a command's usage.
+#[doc(hidden)]
+pub enum Usage<'a> {
+    /// Subcommand with its aliases
+    Command(&'a [&'a str]),
+    /// Single positional argument
+    Value(&'a str),
+    /// Any number of positional arguments
+    Values(&'a str),
+    /// No arguments
+    Empty,
+    /// The `help` subcommand
+    Help,
+}
+
+impl std::fmt::Display for Usage<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Command(names) => names.join(", ").fmt(f),
+            Self::Value(v) => write!(f, "[{v}]"),
+            Self::Values(v) => write!(f, "[{v}].."),
+            Self::Empty => Ok(()),
+            Self::Help => "help [command]".fmt(f),
+        }
+    }
+}
+
+/// The command currently being parsed.
+pub(crate) struct Context<'a> {
+    pub name: String,
+    pub command: &'a Command<'a>,
+    pub root: &'a Command<'a>,
+    pub syntax: Syntax,
+    /// The default config file, if any, shown with the root command's options
+    pub config: Option<String>,
+}
+
+impl Default for Context<'_> {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            command: &Command::EMPTY,
+            root: &Command::EMPTY,
+            syntax: Syntax::default(),
+            config: None,
+        }
+    }
+}
+
+/// Options from a config file, as the index of each option in its command and the arguments
+/// setting it.
+pub(crate) type Entries = Vec<(usize, Vec<String>)>;
+
 /// Argument parser. This can be given to [`parse`](crate::parse) to customise which arguments are
 /// parsed, as well as the formatting.
 pub struct Args {
     args: Vec<String>,
+    name: Option<String>,
     state: State,
+    help: bool,
     pub(crate) style: Style,
-    #[doc(hidden)]
-    pub context: Context<'static>,
+    pub(crate) context: Context<'static>,
+    pub(crate) config: Option<PathBuf>,
+    pub(crate) env: Option<String>,
+    pub(crate) origins: Vec<(Range<usize>, String)>,
+    pub(crate) sections: Vec<(&'static Command<'static>, String, Entries)>,
+    abbreviate: bool,
+    collect_errors: bool,
+    errors: Vec<(Error, Option<Span>)>,
+    /// Where each error last returned by parsing occurred
+    spans: Vec<Option<Span>>,
+    /// The argument and range of the last token or value read
+    span: Option<(usize, Range<usize>)>,
+    /// The argument and range of the last option read
+    opt_span: Option<(usize, Range<usize>)>,
 }
 
 impl Args {
     /// Create an argument parser from command-line arguments.
+    ///
+    /// The program name is taken from the file stem of `argv[0]`, see [`Args::name`].
     #[must_use]
     pub fn new() -> Self {
-        Self::from(std::env::args().skip(1))
+        let mut args = std::env::args();
+        let name = args.next().and_then(|arg0| {
+            let stem = std::path::Path::new(&arg0).file_stem()?;
+            Some(stem.to_string_lossy().into_owned())
+        });
+
+        Self {
+            name,
+            ..Self::from(args)
+        }
     }
 
     /// Create an argument parser from the given arguments.
+    ///
+    /// Unlike [`Args::new`], the first argument is not skipped. The program name defaults to the
+    /// package's name, see [`Args::name`].
     pub fn from(args: impl IntoIterator<Item = String>) -> Self {
         Self {
             args: args.into_iter().collect(),
+            name: None,
             state: State::Read(0),
+            help: false,
             style: Style::default(),
             context: Context::default(),
+            config: None,
+            env: None,
+            origins: Vec::new(),
+            sections: Vec::new(),
+            abbreviate: false,
+            collect_errors: false,
+            errors: Vec::new(),
+            spans: Vec::new(),
+            span: None,
+            opt_span: None,
         }
     }
 
+    /// Write the help message for the command being parsed, as shown by `-h` or `--help`.
+    ///
+    /// This is only available after [`parse`](crate::parse) starts, e.g. from a command's body or
+    /// after [`try_parse`](crate::try_parse) returns.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn render_help(&self, verbosity: Verbosity, f: &mut impl io::Write) -> io::Result<()> {
+        self.style.format_help(&self.context, verbosity, f)
+    }
+
     /// Set this command's [`Style`], changing output colors.
     ///
     /// Color output can be disabled by setting the `NO_COLOR` environment variable, also via
@@ -79,57 +454,623 @@ impl Args {
         self
     }
 
+    /// Set how options are written, e.g. `/out:file` instead of `--out=file`. Definitions are the
+    /// same for every [`Syntax`], and help messages show options in the chosen syntax.
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.context.syntax = syntax;
+        self
+    }
+
+    /// Allow abbreviating long options and subcommands, e.g. `--verb` for `--verbose` or `sta`
+    /// for `status`, as long as only one matches. Hidden options and subcommands must be written
+    /// in full.
+    ///
+    /// Subcommands are only abbreviated if the command doesn't also take positional arguments, so
+    /// values are never mistaken for a subcommand. With [`Syntax::SingleDash`], words made of short
+    /// options are read as those options rather than an abbreviation.
+    pub fn abbreviate(mut self, enabled: bool) -> Self {
+        self.abbreviate = enabled;
+        self
+    }
+
+    /// Keep parsing after an invalid argument, and report every error at once as
+    /// [`Error::Multiple`], instead of stopping at the first one. Commands aren't run if there
+    /// were any errors.
+    pub fn collect_errors(mut self, enabled: bool) -> Self {
+        self.collect_errors = enabled;
+        self
+    }
+
+    /// Set the program name, as shown in help and error messages.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Start parsing the root command. Used by [`parse`](crate::parse).
+    ///
+    /// If no name was set, `default_name` is used as the program name.
+    #[doc(hidden)]
+    pub fn enter_root(&mut self, root: &'static Command<'static>, default_name: &str) {
+        let name = self.name.get_or_insert_with(|| default_name.to_owned());
+
+        self.context = Context {
+            name: name.clone(),
+            command: root,
+            root,
+            syntax: self.context.syntax,
+            config: (self.config.as_ref()).map(|path| path.display().to_string()),
+        };
+
+        // reported once parsing ends, so `--help` still works
+        if let Err(e) = self.load_env().and_then(|()| self.load_config()) {
+            self.errors.push((e, None));
+        }
+    }
+
+    /// Get the program name. Only available after [`parse`](crate::parse) starts.
+    pub(crate) fn program(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Start parsing a subcommand. Used by [`parse`](crate::parse).
+    #[doc(hidden)]
+    pub fn enter(&mut self, name: &str) {
+        if let Some(command) = self.context.command.find(name) {
+            if !self.context.name.is_empty() {
+                self.context.name += " ";
+            }
+            self.context.name += name;
+            self.context.command = command;
+
+            let section = self
+                .sections
+                .iter()
+                .position(|(cmd, ..)| std::ptr::eq(*cmd, command));
+            if let Some(i) = section {
+                let (_, origin, entries) = self.sections.swap_remove(i);
+                self.inject_config(entries, origin);
+            }
+        }
+    }
+
+    /// Insert arguments to be read next, noting where they came from for error messages.
+    pub(crate) fn inject(&mut self, args: Vec<String>, origin: String) {
+        let State::Read(i) = self.state else {
+            return;
+        };
+
+        let len = args.len();
+        self.args.splice(i..i, args);
+
+        for (range, _) in &mut self.origins {
+            if range.start >= i {
+                *range = range.start + len..range.end + len;
+            }
+        }
+
+        self.origins.push((i..i + len, origin));
+    }
+
+    /// Insert options from a config file, leaving out those given in the remaining arguments for
+    /// the current command, so they take precedence.
+    pub(crate) fn inject_config(&mut self, entries: Entries, origin: String) {
+        let given = self.given_options();
+        let args = (entries.into_iter())
+            .filter(|(option, _)| !given.contains(option))
+            .flat_map(|(_, args)| args)
+            .collect();
+
+        self.inject(args, origin);
+    }
+
+    /// Find which options of the current command are given in the remaining arguments, up to its
+    /// first subcommand, without reading them.
+    fn given_options(&mut self) -> Vec<usize> {
+        let saved = (
+            self.state,
+            self.help,
+            self.span.clone(),
+            self.opt_span.clone(),
+        );
+        self.help = false;
+
+        let cmd = self.context.command;
+        let mut given = Vec::new();
+        while let Some(token) = self.read_token() {
+            let name = match token {
+                Token::Value(i) if cmd.find(&self.args[i]).is_some() => break,
+                Token::Value(_) => continue,
+                Token::Short(c) => Opt::Short(c),
+                Token::Name(name, _) => Opt::Long(name),
+                Token::Long(i, name) => match self.expand_long(&self.args[i][name.clone()]) {
+                    Ok(Some(long)) if self.abbreviate => Opt::Long(long),
+                    _ => Opt::Long(&self.args[i][name]),
+                },
+            };
+
+            let option = cmd.options.iter().position(|(opts, ..)| {
+                opts.iter().any(|opt| match (opt, &name) {
+                    (Opt::Short(a), Opt::Short(b)) => a == b,
+                    (Opt::Long(a), Opt::Long(b)) => a == b,
+                    _ => false,
+                })
+            });
+            if let Some(option) = option {
+                for _ in 0..cmd.options[option].1.arity() {
+                    self.value();
+                }
+                given.push(option);
+            }
+        }
+
+        (self.state, self.help, self.span, self.opt_span) = saved;
+        given
+    }
+
+    /// Get the index of the last argument read.
+    fn last(&self) -> Option<usize> {
+        match self.state {
+            State::Read(i) | State::Eoi(i) => i.checked_sub(1),
+            State::Short(i, _) | State::Attached(i, _) => Some(i),
+            State::Empty => self.args.len().checked_sub(1),
+        }
+    }
+
+    /// Get an unexpected option as the user wrote it, e.g. `/Foo` instead of `--foo`.
+    pub(crate) fn written<'s>(&'s self, opt: &'s str) -> &'s str {
+        let Some(arg) = self.last().and_then(|i| self.args.get(i)) else {
+            return opt;
+        };
+
+        // with single dashes, unknown short options are most likely a mistyped long option
+        match (self.state, self.context.syntax) {
+            (State::Short(..), Syntax::SingleDash) => arg.split('=').next().unwrap_or(arg),
+            (State::Short(..), _) => opt,
+            (_, Syntax::Unix | Syntax::SingleDash) => opt,
+            _ if arg == opt => opt,
+            _ => arg
+                .split(self.context.syntax.separator())
+                .next()
+                .unwrap_or(arg),
+        }
+    }
+
+    /// Get the arguments from the given origin, or the command line if `None`, for showing where
+    /// an error occurred.
+    pub(crate) fn words(&self, origin: Option<&str>) -> Vec<&str> {
+        (self.args.iter().enumerate())
+            .filter(|(i, _)| self.origin_of(*i) == origin)
+            .map(|(_, arg)| arg.as_str())
+            .collect()
+    }
+
+    /// Get where the argument at `i` came from, if it wasn't given on the command line.
+    fn origin_of(&self, i: usize) -> Option<&str> {
+        self.origins
+            .iter()
+            .find(|(range, _)| range.contains(&i))
+            .map(|(_, origin)| origin.as_str())
+    }
+
+    /// Get where the last argument read came from, if it wasn't given on the command line.
+    pub(crate) fn origin(&self) -> Option<&str> {
+        self.origin_of(self.last()?)
+    }
+
+    /// Remove a long option and its value from the remaining arguments, before any `--`.
+    pub(crate) fn take_option(&mut self, name: &str) -> Option<String> {
+        let (range, value) = self.find_option(name)?;
+        self.args.drain(range);
+        Some(value)
+    }
+
+    /// Find a long option and its value in the remaining arguments, before any `--`, and get the
+    /// arguments they take up.
+    pub(crate) fn find_option(&self, name: &str) -> Option<(Range<usize>, String)> {
+        let State::Read(start) = self.state else {
+            return None;
+        };
+
+        let end = self.args[start..]
+            .iter()
+            .position(|arg| arg == "--")
+            .map_or(self.args.len(), |i| start + i);
+        let syntax = self.context.syntax;
+        let option = syntax.format(&Opt::Long(name));
+        let prefix = format!("{option}{}", syntax.separator());
+
+        for i in start..end {
+            if self.args[i] == option && i + 1 < end {
+                return Some((i..i + 2, self.args[i + 1].clone()));
+            }
+            if let Some(value) = self.args[i].strip_prefix(&prefix) {
+                return Some((i..i + 1, value.to_owned()));
+            }
+        }
+
+        None
+    }
+
     /// Read the next option in the arguments. Used by [`parse`](crate::parse).
     ///
     /// Returns [None] if empty.
-    pub fn next_opt(&mut self) -> Option<Opt> {
+    pub fn next_opt(&mut self) -> Option<Opt<'_>> {
+        let token = self.next_token()?;
+        Some(self.opt(token))
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.read_token()?;
+        let span = match (&token, self.state) {
+            // include the dash for the first option in a cluster
+            (Token::Short(c), State::Short(i, j)) => match j - c.len_utf8() {
+                1 => Some((i, 0..j)),
+                start => Some((i, start..j)),
+            },
+            // a short option on its own, as in `/v`
+            (Token::Short(_), _) => self.last().map(|i| {
+                let arg = &self.args[i];
+                (i, 0..arg.find(':').unwrap_or(arg.len()))
+            }),
+            (Token::Long(i, name), _) => Some((*i, 0..name.end)),
+            (Token::Value(i), _) => Some((*i, 0..self.args[*i].len())),
+            (Token::Name(_, span), _) => span.clone(),
+        };
+
+        if let Some(span) = span {
+            if !matches!(token, Token::Value(_)) {
+                self.opt_span = Some(span.clone());
+            }
+            self.span = Some(span);
+        }
+
+        Some(token)
+    }
+
+    fn read_token(&mut self) -> Option<Token> {
         loop {
-            match &mut self.state {
-                State::Read(i) => match self.args.get(*i) {
-                    Some(arg) => match arg.strip_prefix('-') {
-                        Some(short) if !short.is_empty() => match short.strip_prefix('-') {
-                            Some(long) if !long.is_empty() => {
-                                *i += 1;
-                                return Some(Opt::Long(long));
-                            }
-                            Some(_) => self.state = State::Eoi(*i + 1),
-                            None => self.state = State::Short(*i, 1),
-                        },
-                        Some(_) => self.state = State::Eoi(*i + 1),
-                        None => {
-                            *i += 1;
-                            return Some(Opt::Value(arg));
-                        }
-                    },
-                    None => self.state = State::Empty,
-                },
-                State::Short(i, j) => match self.args[*i][*j..].chars().next() {
+            match self.state {
+                State::Read(i) if i < self.args.len() => {
+                    if let Some(token) = self.lex(i) {
+                        return Some(token);
+                    }
+                }
+                State::Short(i, j) => match self.args[i][j..].chars().next() {
                     Some(c) => {
-                        *j += c.len_utf8();
-                        return Some(Opt::Short(c));
+                        self.state = State::Short(i, j + c.len_utf8());
+                        return Some(Token::Short(c));
                     }
-                    None => self.state = State::Read(*i + 1),
+                    None => self.state = State::Read(i + 1),
                 },
-                State::Eoi(i) => return self.args.get(*i).map(|v| Opt::Value(v)),
+                // the option didn't take its value
+                State::Attached(i, _) => self.state = State::Read(i + 1),
+                State::Eoi(i) if i < self.args.len() => {
+                    self.state = State::Eoi(i + 1);
+                    return Some(Token::Value(i));
+                }
+                State::Read(_) | State::Eoi(_) => self.state = State::Empty,
+                State::Empty if self.help => {
+                    self.help = false;
+                    return Some(Token::Name("help", None));
+                }
                 State::Empty => return None,
             }
         }
     }
 
+    fn opt(&self, token: Token) -> Opt<'_> {
+        match token {
+            Token::Short(c) => Opt::Short(c),
+            Token::Long(i, name) => Opt::Long(&self.args[i][name]),
+            Token::Value(i) => Opt::Value(&self.args[i]),
+            Token::Name(name, _) => Opt::Long(name),
+        }
+    }
+
+    /// Expand an abbreviated long option, if it isn't an option already.
+    fn expand_long(&self, name: &str) -> Result<Option<&'static str>, Error> {
+        let cmd = self.context.command;
+        if cmd.has_long(name) {
+            return Ok(None);
+        }
+
+        // abbreviations ignore case like full names do
+        let starts_with = |long: &str| match self.context.syntax {
+            Syntax::Dos => long.to_lowercase().starts_with(&name.to_lowercase()),
+            _ => long.starts_with(name),
+        };
+
+        let visible = cmd.options.iter().filter(|(.., hidden)| !hidden);
+        let mut candidates: Vec<&'static str> = visible
+            .filter_map(|(opts, ..)| {
+                opts.iter().find_map(|opt| match opt {
+                    Opt::Long(long) if starts_with(long) => Some(*long),
+                    _ => None,
+                })
+            })
+            .collect();
+        if starts_with("help") {
+            candidates.push("help");
+        }
+
+        match candidates[..] {
+            [] => Ok(None),
+            [long] => Ok(Some(long)),
+            _ => Err(Error::Ambiguous(
+                self.context.syntax.format(&Opt::Long(name)),
+                (candidates.iter())
+                    .map(|long| self.context.syntax.format(&Opt::Long(long)))
+                    .collect(),
+            )),
+        }
+    }
+
+    /// Check if a single-dash word should be expanded as a long option, or reported as ambiguous,
+    /// instead of being read as short options.
+    fn abbreviates(&self, name: &str) -> bool {
+        let cmd = self.context.command;
+        self.abbreviate
+            && !name.chars().all(|c| cmd.has_short(c))
+            && !matches!(self.expand_long(name), Ok(None))
+    }
+
+    /// Expand an abbreviated subcommand, if enabled with [`Args::abbreviate`]. Used by
+    /// [`parse`](crate::parse).
+    #[doc(hidden)]
+    pub fn expand_command(&self, name: String) -> Result<String, Error> {
+        let cmd = self.context.command;
+        if !self.abbreviate
+            || name.is_empty()
+            || name == "help"
+            || cmd.has_positionals()
+            || cmd.find(&name).is_some()
+        {
+            return Ok(name);
+        }
+
+        let visible = cmd.commands.iter().filter(|sub| !sub.hidden);
+        let mut candidates: Vec<&str> = visible
+            .filter_map(|sub| sub.names.iter().find(|n| n.starts_with(&name)).copied())
+            .collect();
+        if "help".starts_with(&name) && cmd.has_help_command() {
+            candidates.push("help");
+        }
+
+        match candidates[..] {
+            [] => Ok(name),
+            [sub] => Ok(sub.to_owned()),
+            _ => Err(Error::Ambiguous(
+                name,
+                candidates.into_iter().map(str::to_owned).collect(),
+            )),
+        }
+    }
+
+    /// Read the argument at `i` with the current syntax. Returns [`None`] if it only changes the
+    /// state, e.g. with `--`.
+    fn lex(&mut self, i: usize) -> Option<Token> {
+        let arg = &self.args[i];
+        self.state = State::Read(i + 1);
+
+        match self.context.syntax {
+            Syntax::Unix => self.lex_unix(i),
+            Syntax::KeyValue => match arg.split_once('=') {
+                Some((key, _))
+                    if self
+                        .context
+                        .command
+                        .long_kind(key)
+                        .is_some_and(Kind::attaches) =>
+                {
+                    self.state = State::Attached(i, key.len() + 1);
+                    Some(Token::Long(i, 0..key.len()))
+                }
+                _ => self.lex_unix(i),
+            },
+            Syntax::SingleDash => {
+                let name = (arg.strip_prefix('-'))
+                    .filter(|word| !word.starts_with('-'))
+                    .map(|word| word.split('=').next().unwrap_or(word));
+
+                match name {
+                    Some(name) if self.context.command.has_long(name) || self.abbreviates(name) => {
+                        let end = 1 + name.len();
+                        if end < arg.len() {
+                            self.state = State::Attached(i, end + 1);
+                        }
+                        Some(Token::Long(i, 1..end))
+                    }
+                    _ => self.lex_unix(i),
+                }
+            }
+            Syntax::Dos => match arg.strip_prefix('/') {
+                Some(rest) if !rest.is_empty() => {
+                    let end = arg.find(':').unwrap_or(arg.len());
+                    let name = &arg[1..end];
+                    if end < arg.len() {
+                        self.state = State::Attached(i, end + 1);
+                    }
+
+                    // names are matched ignoring case, but read as they're defined
+                    let cmd = self.context.command;
+                    let mut chars = name.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some('?'), None) => Some(Token::Name("help", Some((i, 0..end)))),
+                        (Some(c), None) => {
+                            Some(Token::Short(cmd.find_short_ignore_case(c).unwrap_or(c)))
+                        }
+                        _ => match cmd.find_long_ignore_case(name) {
+                            Some(long) => Some(Token::Name(long, Some((i, 0..end)))),
+                            None => Some(Token::Long(i, 1..end)),
+                        },
+                    }
+                }
+                _ => Some(Token::Value(i)),
+            },
+        }
+    }
+
+    /// Read the argument at `i` with [`Syntax::Unix`].
+    fn lex_unix(&mut self, i: usize) -> Option<Token> {
+        let arg = &self.args[i];
+        self.state = State::Read(i + 1);
+
+        match arg.strip_prefix('-') {
+            Some("" | "-") => {
+                self.state = State::Eoi(i + 1);
+                None
+            }
+            Some(short) if !short.starts_with('-') => {
+                self.state = State::Short(i, 1);
+                None
+            }
+            Some(_) => match arg.find('=') {
+                Some(end) => {
+                    self.state = State::Attached(i, end + 1);
+                    Some(Token::Long(i, 2..end))
+                }
+                None => Some(Token::Long(i, 2..arg.len())),
+            },
+            None => Some(Token::Value(i)),
+        }
+    }
+
+    /// Request help once all arguments are read, as if `--help` was given last. Used by
+    /// [`parse`](crate::parse) to implement the `help <command>` subcommand.
+    #[doc(hidden)]
+    pub fn request_help(&mut self) {
+        self.help = true;
+    }
+
+    /// Check if `help <command>` is a subcommand here. Used by [`parse`](crate::parse).
+    #[doc(hidden)]
+    pub fn has_help_command(&self) -> bool {
+        self.context.command.has_help_command()
+    }
+
+    /// Warn that a deprecated option was used. Used by [`parse`](crate::parse).
+    #[doc(hidden)]
+    pub fn deprecated(&self, opt: &str, note: &str) {
+        let mut warning = format!("option '{opt}' is deprecated");
+        if !note.is_empty() {
+            warning = format!("{warning}, {note}");
+        }
+
+        let _ = self
+            .style
+            .format_warning(&warning, &mut io::stderr().lock());
+    }
+
+    /// Read the next option, or an error such as an ambiguous abbreviation. Used by
+    /// [`parse`](crate::parse).
+    #[doc(hidden)]
+    pub fn try_next_opt(&mut self) -> Result<Option<Opt<'_>>, Error> {
+        // a value was given to an option that doesn't take one
+        if let State::Attached(i, _) = self.state {
+            self.state = State::Read(i + 1);
+            self.span = Some((i, 0..self.args[i].len()));
+            return Err(Error::Unexpected(self.args[i].clone()));
+        }
+
+        let Some(mut token) = self.next_token() else {
+            return Ok(None);
+        };
+        if let (true, Token::Long(i, name)) = (self.abbreviate, &token) {
+            if let Some(long) = self.expand_long(&self.args[*i][name.clone()])? {
+                token = Token::Name(long, None);
+            }
+        }
+
+        Ok(Some(self.opt(token)))
+    }
+
     /// Get the next value from the arguments. Used by [`parse`](crate::parse).
     ///
     /// Similar to `args.next_opt().map(|v| v.to_string())`, but also handles values in short
     /// arguments (`-ofile`).
     pub fn value(&mut self) -> Option<String> {
         match self.state {
-            State::Short(i, j) => {
+            State::Short(i, j) if j < self.args[i].len() => {
+                self.state = State::Read(i + 1);
+                self.span = Some((i, j..self.args[i].len()));
+                Some(self.args[i][j..].to_owned())
+            }
+            State::Short(i, _) => {
                 self.state = State::Read(i + 1);
+                self.value()
+            }
+            State::Attached(i, j) => {
+                self.state = State::Read(i + 1);
+                self.span = Some((i, j..self.args[i].len()));
                 Some(self.args[i][j..].to_owned())
             }
+            State::Read(i) if i < self.args.len() => {
+                self.state = State::Read(i + 1);
+                self.span = Some((i, 0..self.args[i].len()));
+                Some(self.args[i].clone())
+            }
             _ => self.next_opt().map(|v| v.to_string()),
         }
     }
 
+    /// Get a value in the same argument as the last option, e.g. `--name=value` or `-nvalue`. Used
+    /// by [`parse`](crate::parse).
+    pub fn attached_value(&mut self) -> Option<String> {
+        match self.state {
+            State::Short(i, j) if j < self.args[i].len() => {
+                self.state = State::Read(i + 1);
+                self.span = Some((i, j..self.args[i].len()));
+                Some(self.args[i][j..].to_owned())
+            }
+            State::Attached(i, j) => {
+                self.state = State::Read(i + 1);
+                self.span = Some((i, j..self.args[i].len()));
+                Some(self.args[i][j..].to_owned())
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the next `N` values. Used by [`parse`](crate::parse).
+    ///
+    /// # Errors
+    ///
+    /// If there are fewer values, returning how many were found.
+    pub fn values<const N: usize>(&mut self) -> Result<[String; N], usize> {
+        let mut values = Vec::with_capacity(N);
+        for _ in 0..N {
+            match self.value() {
+                Some(value) => values.push(value),
+                None => return Err(values.len()),
+            }
+        }
+
+        Ok(values.try_into().unwrap_or_else(|_| unreachable!()))
+    }
+
+    /// Get the next value, split on `delimiter`. Used by [`parse`](crate::parse).
+    ///
+    /// The delimiter can be escaped with a backslash, e.g. `a\,b`, as can backslashes themselves.
+    pub fn list_value(&mut self, delimiter: char) -> Option<Vec<String>> {
+        let value = self.value()?;
+        let mut values = vec![String::new()];
+        let mut chars = value.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next_if(|&next| next == delimiter || next == '\\') {
+                    Some(next) => values.last_mut().unwrap().push(next),
+                    None => values.last_mut().unwrap().push(c),
+                },
+                c if c == delimiter => values.push(String::new()),
+                c => values.last_mut().unwrap().push(c),
+            }
+        }
+
+        Some(values)
+    }
+
     /// Try to read all the remaining arguments as values. Used by [`parse`](crate::parse).
     ///
     /// # Errors
@@ -147,6 +1088,159 @@ impl Args {
         }
     }
 
+    /// Read the remaining arguments as values, converting each one, after the already read
+    /// `first` value. Used by [`parse`](crate::parse).
+    ///
+    /// # Errors
+    ///
+    /// If a non-[value](Opt::Value) argument is found, or if converting a value fails.
+    #[doc(hidden)]
+    pub fn collect_values<T>(
+        &mut self,
+        first: Option<String>,
+        mut convert: impl FnMut(String) -> Result<T, Error>,
+    ) -> Result<Vec<T>, Error> {
+        let mut values = Vec::new();
+        if let Some(first) = first {
+            values.push(convert(first)?);
+        }
+
+        loop {
+            let opt = match self.next_opt() {
+                Some(Opt::Value(v)) => {
+                    let v = v.to_owned();
+                    values.push(convert(v)?);
+                    continue;
+                }
+                Some(opt) => opt.to_string(),
+                None => return Ok(values),
+            };
+
+            // `help <command>` was given before the values, see [`Args::request_help`]
+            if opt == "--help" && matches!(self.state, State::Empty) {
+                return Err(Error::Help(Verbosity::Long));
+            }
+            return Err(Error::Unexpected(opt));
+        }
+    }
+
+    /// Record an error, if collecting errors and there are more arguments to check. Otherwise,
+    /// return it along with any errors found before. Used by [`parse`](crate::parse).
+    ///
+    /// Errors caused by an argument are given its location, see [`Args::error_span`].
+    ///
+    /// # Errors
+    ///
+    /// If parsing should stop.
+    pub fn recover(&mut self, error: Error) -> Result<(), Error> {
+        if let Error::Help(_) | Error::HelpJson = error {
+            self.errors.clear();
+            return Err(error);
+        }
+
+        let (error, span) = self.locate(error);
+
+        // unknown options are reported with their value, and with single dashes unknown words are
+        // reported whole, so skip the rest of the argument
+        match (&error, &span, self.state, self.context.syntax) {
+            (Error::Unexpected(_), Some(_), State::Attached(i, _), _)
+            | (Error::Unexpected(_), Some(_), State::Short(i, _), Syntax::SingleDash) => {
+                self.state = State::Read(i + 1);
+            }
+            _ => {}
+        }
+        match error {
+            Error::Multiple(errors) => {
+                let spans = std::mem::take(&mut self.spans).into_iter();
+                let spans = spans.chain(std::iter::repeat(None));
+                self.errors.extend(errors.into_iter().zip(spans));
+            }
+            error => self.errors.push((error, span)),
+        }
+
+        if self.collect_errors && self.has_next() {
+            Ok(())
+        } else {
+            self.take_errors()
+        }
+    }
+
+    /// Return the errors found so far, if any. Used by [`parse`](crate::parse) before running a
+    /// command.
+    ///
+    /// # Errors
+    ///
+    /// If any errors were recorded with [`Args::recover`].
+    pub fn take_errors(&mut self) -> Result<(), Error> {
+        let (mut errors, spans): (Vec<_>, _) = std::mem::take(&mut self.errors).into_iter().unzip();
+        self.spans = spans;
+
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(Error::Multiple(errors)),
+        }
+    }
+
+    /// Get where the error returned by parsing occurred, if it was caused by an argument. With
+    /// [`Error::Multiple`], this is where the first error occurred, see [`Args::error_spans`].
+    pub fn error_span(&self) -> Option<&Span> {
+        self.spans.first()?.as_ref()
+    }
+
+    /// Get where each error returned by parsing occurred, in the same order as in
+    /// [`Error::Multiple`].
+    pub fn error_spans(&self) -> &[Option<Span>] {
+        &self.spans
+    }
+
+    /// Add the location of the argument that caused an error.
+    fn locate(&self, error: Error) -> (Error, Option<Span>) {
+        let span = match error {
+            Error::MissingValue | Error::MissingValues { .. } => &self.opt_span,
+            Error::Unexpected(_)
+            | Error::UnknownCommand(_)
+            | Error::Invalid { .. }
+            | Error::Ambiguous(..) => &self.span,
+            _ => return (error, None),
+        };
+        let Some((i, mut range)) = span.clone() else {
+            return (error, None);
+        };
+        if let (Error::Unexpected(_), State::Short(..), Syntax::SingleDash) =
+            (&error, self.state, self.context.syntax)
+        {
+            range = 0..self.args[i].find('=').unwrap_or(self.args[i].len());
+        }
+
+        // messages can't rely on the parser's state once it moves on
+        let error = match error {
+            Error::Unexpected(v) => Error::Unexpected(self.written(&v).to_owned()),
+            error => error,
+        };
+
+        let origin = self.origin_of(i);
+        let index = (0..i).filter(|&j| self.origin_of(j) == origin).count();
+        let span = Span {
+            origin: origin.map(ToOwned::to_owned),
+            index,
+            range,
+        };
+
+        (error, Some(span))
+    }
+
+    /// Whether there may be more arguments to read.
+    fn has_next(&self) -> bool {
+        let len = self.args.len();
+        match self.state {
+            State::Read(i) | State::Eoi(i) => i < len,
+            State::Short(i, j) => j < self.args[i].len() || i + 1 < len,
+            State::Attached(i, _) => i + 1 < len,
+            State::Empty => self.help,
+        }
+    }
+
     /// Peek the previous argument. Used by [`parse`](crate::parse) when formatting errors.
     pub fn peek_back(&self) -> Option<&str> {
         match self.state {
@@ -162,7 +1256,28 @@ impl Default for Args {
     }
 }
 
+/// Where an error occurred in the arguments, see [`Args::error_span`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// Where the argument came from, e.g. `$TOOL_OPTS`, or `None` for the command line
+    pub origin: Option<String>,
+    /// Index of the argument among those from the same origin, not counting the program name
+    pub index: usize,
+    /// Byte range of the offending part of the argument
+    pub range: Range<usize>,
+}
+
+/// How much detail to include in the help message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Only the first paragraph of each doc comment, shown with `-h`
+    Short,
+    /// Full doc comments, shown with `--help`
+    Long,
+}
+
 /// Colors for [`Error`](crate::Error) output.
+#[derive(Clone, Copy)]
 pub struct Style {
     /// Primary color, used in headers
     pub primary: Color,
@@ -172,33 +1287,53 @@ pub struct Style {
     pub tertiary: Color,
     /// Error color, in the error message's prefix
     pub error: Color,
+    /// Warning color, in the warning message's prefix
+    pub warning: Color,
 }
 
 impl Style {
-    pub(crate) fn format_help(&self, ctx: &Context, f: &mut impl io::Write) -> io::Result<()> {
+    /// A style without any colors.
+    #[must_use]
+    pub fn plain() -> Self {
+        Self {
+            primary: Color(None),
+            secondary: Color(None),
+            tertiary: Color(None),
+            error: Color(None),
+            warning: Color(None),
+        }
+    }
+
+    pub(crate) fn format_help(
+        &self,
+        ctx: &Context,
+        verbosity: Verbosity,
+        f: &mut impl io::Write,
+    ) -> io::Result<()> {
         let name = &ctx.name;
-        let description = ctx.description.trim();
+        let cmd = ctx.command;
+        let doc = |doc: &str, indent: usize| match verbosity {
+            Verbosity::Short => summary(doc),
+            Verbosity::Long => details(doc, indent),
+        };
+        let description = doc(cmd.description, 0);
 
-        let &Style {
-            primary: mut p,
-            secondary: mut s,
-            tertiary: mut t,
+        let Style {
+            primary: p,
+            secondary: s,
+            tertiary: t,
             ..
-        } = self;
-
-        if std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
-            p.disable();
-            s.disable();
-            t.disable();
-        }
+        } = self.resolve();
 
-        if ctx.usages.is_empty() {
+        let usages = cmd.usages.iter().filter(|(.., hidden)| !hidden);
+        if usages.clone().next().is_none() {
             writeln!(f, "{p}Usage: {s}{name}")?;
         } else {
             writeln!(f, "{p}Usage:")?;
-            for (usage, doc) in ctx.usages {
-                let cmd = format!("{name} {usage}");
-                writeln!(f, "    {s}{cmd:<18}  {t}{}", doc.trim())?;
+            for (usage, usage_doc, _) in usages {
+                let usage = format!("{name} {usage}");
+                let usage = usage.trim_start();
+                writeln!(f, "    {s}{usage:<18}  {t}{}", doc(usage_doc, 24))?;
             }
         }
 
@@ -206,33 +1341,150 @@ impl Style {
             writeln!(f, "\n{t}{description}")?;
         }
 
-        if !ctx.options.is_empty() {
+        // `--config` is read before parsing, so it isn't part of the definition unless declared
+        let config = (ctx.config.as_ref())
+            .filter(|_| std::ptr::eq(cmd, ctx.root) && !cmd.has_long("config"))
+            .map(|path| format!("Read options from this file instead of {path}"));
+        let config = (config.iter()).map(|doc| {
+            let opts: &[Opt] = &[Opt::Long("config")];
+            (opts, Kind::Value, "path", doc.as_str())
+        });
+
+        let options = (cmd.options.iter())
+            .filter(|(.., hidden)| !hidden)
+            .map(|(opts, kind, value_name, doc, _)| (*opts, *kind, *value_name, *doc))
+            .chain(config);
+        if options.clone().next().is_some() {
             writeln!(f, "\n{p}Options:{t}")?;
-            for (opts, v, doc) in ctx.options {
-                let opts = opts
-                    .iter()
-                    .map(Opt::to_string)
-                    .collect::<Vec<_>>()
-                    .join(", ")
-                    + v;
+            for (opts, kind, value_name, opt_doc) in options {
+                let opts = ctx.syntax.format_names(opts, kind, value_name);
 
-                writeln!(f, "    {opts:<22}  {}", doc.trim())?;
+                writeln!(f, "    {opts:<22}  {}", doc(opt_doc, 28))?;
             }
         }
 
-        if !ctx.commands.is_empty() {
-            writeln!(f, "\n{p}Commands:{t}")?;
-            for (cmd, doc) in ctx.commands {
-                writeln!(f, "    {cmd:<22}  {}", doc.trim())?;
+        if verbosity == Verbosity::Long && !cmd.examples.is_empty() {
+            writeln!(f, "\n{p}Examples:")?;
+            for (example, example_doc) in cmd.examples {
+                writeln!(f, "    {s}{example}{t}")?;
+                if !example_doc.is_empty() {
+                    writeln!(f, "        {}", details(example_doc, 8))?;
+                }
             }
         }
 
+        if !cmd.after_help.is_empty() {
+            writeln!(f, "\n{t}{}", details(cmd.after_help, 0))?;
+        }
+
         Ok(())
     }
 
-    pub(crate) fn format_error(&self, error: &str, f: &mut impl io::Write) -> io::Result<()> {
-        writeln!(f, "{}error: {}{error}", self.error, self.tertiary)
+    /// Write an error message, prefixed with `error:`.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn format_error(&self, error: &str, f: &mut impl io::Write) -> io::Result<()> {
+        let style = self.resolve();
+        writeln!(f, "{}error: {}{error}", style.error, style.tertiary)
     }
+
+    /// Write the arguments with part of one underlined, to show where an error occurred. The
+    /// underlined part is given by `span`, where `words` are the arguments from its origin.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn format_span(
+        &self,
+        words: &[&str],
+        span: &Span,
+        f: &mut impl io::Write,
+    ) -> io::Result<()> {
+        let style = self.resolve();
+
+        let mut line = String::new();
+        let mut start = 0;
+        let mut len = 0;
+        for (i, word) in words.iter().enumerate() {
+            if i != 0 {
+                line.push(' ');
+            }
+
+            // quote words that would otherwise be unclear, keeping the span in place
+            let quoted = word.is_empty() || word.contains(char::is_whitespace);
+            if i == span.index {
+                let range = span.range.start.min(word.len())..span.range.end.min(word.len());
+                start = line.chars().count()
+                    + usize::from(quoted)
+                    + word[..range.start].chars().count();
+                len = word[range].chars().count().max(1);
+            }
+
+            if quoted {
+                line += &format!("'{word}'");
+            } else {
+                line += word;
+            }
+        }
+
+        writeln!(f, "    {line}")?;
+        if len == 0 {
+            return Ok(());
+        }
+        writeln!(
+            f,
+            "    {}{}{}{}",
+            " ".repeat(start),
+            style.error,
+            "^".repeat(len),
+            style.tertiary,
+        )
+    }
+
+    /// Write a warning message, prefixed with `warning:`.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn format_warning(&self, warning: &str, f: &mut impl io::Write) -> io::Result<()> {
+        let style = self.resolve();
+        writeln!(f, "{}warning: {}{warning}", style.warning, style.tertiary)
+    }
+
+    /// Get the colors to output, which are all disabled if `NO_COLOR` is set.
+    fn resolve(&self) -> Self {
+        if std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty()) {
+            Self::plain()
+        } else {
+            *self
+        }
+    }
+}
+
+/// Get the first paragraph of a doc comment, as a single line.
+pub(crate) fn summary(doc: &str) -> String {
+    doc.trim_start()
+        .lines()
+        .map(str::trim)
+        .take_while(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Get the full text of a doc comment, indenting every line after the first.
+pub(crate) fn details(doc: &str, indent: usize) -> String {
+    doc.trim()
+        .lines()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
+        .enumerate()
+        .map(|(i, line)| match line {
+            _ if i == 0 || line.is_empty() => line.to_owned(),
+            _ => format!("{:indent$}{line}", ""),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
 }
 
 impl Default for Style {
@@ -242,6 +1494,7 @@ impl Default for Style {
             secondary: Color::new("96"),
             tertiary: Color::new(""),
             error: Color::new("31"),
+            warning: Color::new("33"),
         }
     }
 }
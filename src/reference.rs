@@ -0,0 +1,450 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io;
+
+use crate::parser::{details, summary, Command, Kind, Usage};
+use crate::{Args, Syntax};
+
+/// Write a reference for a command tree without parsing any arguments, e.g. from a build script or
+/// a separate binary. This takes the same definitions as [`parse`](crate::parse), whose bodies are
+/// never run, and gives a [`Reference`].
+///
+/// ```
+/// let reference = sylveon::reference! {
+///     //! A tiny package manager
+///
+///     /// Add a dependency
+///     "add" {
+///         name => todo!(),
+///     },
+/// };
+///
+/// let mut out = Vec::new();
+/// reference.name("pkg").write_markdown(&mut out).unwrap();
+/// ```
+#[macro_export]
+macro_rules! reference {
+    ($($rest:tt)*) => {{
+        use $crate::__priv::*;
+
+        const __COMMAND: $crate::__priv::Command<'static> = $crate::__command! { [] []; $($rest)* };
+        $crate::Reference::new(&__COMMAND, ::std::env!("CARGO_PKG_NAME"))
+    }};
+}
+
+/// A command tree, to write references for. Made with [`reference`](crate::reference), or with
+/// [`Args::reference`] once parsing starts.
+pub struct Reference {
+    root: &'static Command<'static>,
+    name: String,
+    syntax: Syntax,
+}
+
+impl Reference {
+    /// Create a reference for a command tree. Used by [`reference`](crate::reference).
+    #[doc(hidden)]
+    pub fn new(root: &'static Command<'static>, name: &str) -> Self {
+        Self {
+            root,
+            name: name.to_owned(),
+            syntax: Syntax::default(),
+        }
+    }
+
+    /// Set the program name, instead of the package's name.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Set how options are written, see [`Args::syntax`].
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Write a Markdown reference for the whole command tree, with a section for each
+    /// subcommand. Hidden options and commands are left out.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_markdown(&self, f: &mut impl io::Write) -> io::Result<()> {
+        let mut sections = Vec::new();
+        walk(self.root, &self.name, &mut sections);
+
+        for (i, (name, cmd)) in sections.into_iter().enumerate() {
+            let heading = if i == 0 { "#" } else { "##" };
+            if i != 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{heading} `{name}`")?;
+
+            let description = details(cmd.description, 0);
+            if !description.is_empty() {
+                writeln!(f, "\n{description}")?;
+            }
+
+            writeln!(f, "\n```text")?;
+            for usage in usages(&name, cmd) {
+                writeln!(f, "{usage}")?;
+            }
+            writeln!(f, "```")?;
+
+            let options = options(cmd, self.syntax);
+            if !options.is_empty() {
+                writeln!(f, "\n| Option | Description |\n| --- | --- |")?;
+                for (opts, doc) in options {
+                    writeln!(f, "| `{opts}` | {} |", summary(doc).replace('|', "\\|"))?;
+                }
+            }
+
+            let commands = commands(cmd);
+            if !commands.is_empty() {
+                writeln!(f, "\n| Command | Description |\n| --- | --- |")?;
+                for sub in commands {
+                    let names = sub.names.join("`, `");
+                    let doc = summary(sub.description).replace('|', "\\|");
+                    writeln!(f, "| `{names}` | {doc} |")?;
+                }
+            }
+
+            if !cmd.examples.is_empty() {
+                writeln!(f, "\nExamples:")?;
+                for (example, doc) in cmd.examples {
+                    if !doc.is_empty() {
+                        writeln!(f, "\n{}", details(doc, 0))?;
+                    }
+                    writeln!(f, "\n```sh\n{example}\n```")?;
+                }
+            }
+
+            if !cmd.after_help.is_empty() {
+                writeln!(f, "\n{}", details(cmd.after_help, 0))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write an HTML reference for the whole command tree, similar to
+    /// [`write_markdown`](Reference::write_markdown). Each subcommand is a `<section>`, without
+    /// any surrounding document or styles.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_html(&self, f: &mut impl io::Write) -> io::Result<()> {
+        let mut sections = Vec::new();
+        walk(self.root, &self.name, &mut sections);
+
+        for (name, cmd) in sections {
+            let id = name.replace(' ', "-");
+            writeln!(f, "<section id=\"{}\">", escape(&id))?;
+            writeln!(f, "<h2><code>{}</code></h2>", escape(&name))?;
+
+            let description = details(cmd.description, 0);
+            for paragraph in description.split("\n\n").filter(|p| !p.is_empty()) {
+                writeln!(f, "<p>{}</p>", escape(paragraph))?;
+            }
+
+            writeln!(f, "<pre>")?;
+            for usage in usages(&name, cmd) {
+                writeln!(f, "{}", escape(&usage))?;
+            }
+            writeln!(f, "</pre>")?;
+
+            let options = options(cmd, self.syntax);
+            if !options.is_empty() {
+                writeln!(f, "<dl class=\"options\">")?;
+                for (opts, doc) in options {
+                    writeln!(f, "<dt><code>{}</code></dt>", escape(&opts))?;
+                    writeln!(f, "<dd>{}</dd>", escape(&summary(doc)))?;
+                }
+                writeln!(f, "</dl>")?;
+            }
+
+            let commands = commands(cmd);
+            if !commands.is_empty() {
+                writeln!(f, "<dl class=\"commands\">")?;
+                for sub in commands {
+                    let href = format!("{id}-{}", sub.names[0]);
+                    let names = sub.names.join(", ");
+                    writeln!(
+                        f,
+                        "<dt><a href=\"#{}\"><code>{}</code></a></dt>",
+                        escape(&href),
+                        escape(&names),
+                    )?;
+                    writeln!(f, "<dd>{}</dd>", escape(&summary(sub.description)))?;
+                }
+                writeln!(f, "</dl>")?;
+            }
+
+            if !cmd.examples.is_empty() {
+                writeln!(f, "<dl class=\"examples\">")?;
+                for (example, doc) in cmd.examples {
+                    writeln!(f, "<dt><code>{}</code></dt>", escape(example))?;
+                    if !doc.is_empty() {
+                        writeln!(f, "<dd>{}</dd>", escape(&summary(doc)))?;
+                    }
+                }
+                writeln!(f, "</dl>")?;
+            }
+
+            if !cmd.after_help.is_empty() {
+                writeln!(f, "<p>{}</p>", escape(&details(cmd.after_help, 0)))?;
+            }
+
+            writeln!(f, "</section>")?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the whole command tree as JSON, including hidden options and commands. This is meant
+    /// for other tools, e.g. to build a UI on top of the command.
+    ///
+    /// Every command also accepts a hidden `--help-json` option, which prints the JSON like
+    /// `--help` prints the help message, so tools can get it without running anything.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_json(&self, f: &mut impl io::Write) -> io::Result<()> {
+        json(self.root, &self.name).write(f, 0)?;
+        writeln!(f)
+    }
+}
+
+impl Args {
+    /// Get the command tree being parsed, to write references for. This is only available after
+    /// [`parse`](crate::parse) starts, e.g. from a command's body; see
+    /// [`reference`](crate::reference) to get it without parsing.
+    #[must_use]
+    pub fn reference(&self) -> Reference {
+        Reference {
+            root: self.context.root,
+            name: self.program().to_owned(),
+            syntax: self.context.syntax,
+        }
+    }
+
+    /// Write a Markdown reference, see [`Reference::write_markdown`]. Only available after
+    /// [`parse`](crate::parse) starts.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_markdown(&self, f: &mut impl io::Write) -> io::Result<()> {
+        self.reference().write_markdown(f)
+    }
+
+    /// Write an HTML reference, see [`Reference::write_html`]. Only available after
+    /// [`parse`](crate::parse) starts.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_html(&self, f: &mut impl io::Write) -> io::Result<()> {
+        self.reference().write_html(f)
+    }
+
+    /// Write the command tree as JSON, see [`Reference::write_json`]. Only available after
+    /// [`parse`](crate::parse) starts.
+    ///
+    /// # Errors
+    ///
+    /// If writing to `f` fails.
+    pub fn write_json(&self, f: &mut impl io::Write) -> io::Result<()> {
+        self.reference().write_json(f)
+    }
+}
+
+enum Json {
+    Str(String),
+    Bool(bool),
+    Num(usize),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn str(text: &str) -> Self {
+        Self::Str(text.to_owned())
+    }
+
+    fn write(&self, f: &mut impl io::Write, indent: usize) -> io::Result<()> {
+        let pad = "  ".repeat(indent + 1);
+
+        match self {
+            Self::Str(text) => {
+                write!(f, "\"")?;
+                for c in text.chars() {
+                    match c {
+                        '"' => write!(f, "\\\"")?,
+                        '\\' => write!(f, "\\\\")?,
+                        '\n' => write!(f, "\\n")?,
+                        c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                        c => write!(f, "{c}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Num(n) => write!(f, "{n}"),
+            Self::Array(items) if items.is_empty() => write!(f, "[]"),
+            Self::Array(items) => {
+                writeln!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    write!(f, "{pad}")?;
+                    item.write(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < items.len() { "," } else { "" })?;
+                }
+                write!(f, "{}]", "  ".repeat(indent))
+            }
+            Self::Object(fields) => {
+                writeln!(f, "{{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    write!(f, "{pad}\"{key}\": ")?;
+                    value.write(f, indent + 1)?;
+                    writeln!(f, "{}", if i + 1 < fields.len() { "," } else { "" })?;
+                }
+                write!(f, "{}}}", "  ".repeat(indent))
+            }
+        }
+    }
+}
+
+fn json(cmd: &Command, name: &str) -> Json {
+    let options = cmd
+        .options
+        .iter()
+        .map(|(opts, kind, value_name, doc, hidden)| {
+            let kind_name = match kind {
+                Kind::Switch => "switch",
+                Kind::Count => "count",
+                Kind::Value => "value",
+                Kind::List(_) => "list",
+                Kind::Values(_) => "values",
+                Kind::Optional(_) => "optional",
+                Kind::Map => "map",
+            };
+
+            let mut option = vec![
+                (
+                    "names",
+                    Json::Array(opts.iter().map(|o| Json::Str(o.to_string())).collect()),
+                ),
+                ("kind", Json::str(kind_name)),
+            ];
+            if kind.attaches() {
+                option.push(("value_name", Json::str(kind.value_name(value_name))));
+            }
+            if let Kind::List(delimiter) = kind {
+                option.push(("delimiter", Json::Str(delimiter.to_string())));
+            }
+            if let Kind::Values(n) = kind {
+                option.push(("count", Json::Num(*n)));
+            }
+            if let Kind::Optional(implicit) = kind {
+                option.push(("implicit", Json::str(implicit)));
+            }
+            option.push(("description", Json::Str(details(doc, 0))));
+            option.push(("hidden", Json::Bool(*hidden)));
+
+            Json::Object(option)
+        });
+
+    let positionals = cmd.usages.iter().filter_map(|(usage, doc, hidden)| {
+        let (name, multiple) = match usage {
+            Usage::Value(name) => (name, false),
+            Usage::Values(name) => (name, true),
+            _ => return None,
+        };
+
+        Some(Json::Object(vec![
+            ("name", Json::str(name)),
+            ("multiple", Json::Bool(multiple)),
+            ("description", Json::Str(details(doc, 0))),
+            ("hidden", Json::Bool(*hidden)),
+        ]))
+    });
+
+    let examples = cmd.examples.iter().map(|(example, doc)| {
+        Json::Object(vec![
+            ("command", Json::str(example)),
+            ("description", Json::Str(details(doc, 0))),
+        ])
+    });
+
+    let aliases = cmd.names.iter().skip(1).map(|alias| Json::str(alias));
+
+    Json::Object(vec![
+        ("name", Json::str(name)),
+        ("aliases", Json::Array(aliases.collect())),
+        ("description", Json::Str(details(cmd.description, 0))),
+        ("hidden", Json::Bool(cmd.hidden)),
+        ("options", Json::Array(options.collect())),
+        ("positionals", Json::Array(positionals.collect())),
+        (
+            "commands",
+            Json::Array(
+                cmd.commands
+                    .iter()
+                    .map(|sub| json(sub, sub.names[0]))
+                    .collect(),
+            ),
+        ),
+        ("examples", Json::Array(examples.collect())),
+        ("after_help", Json::Str(details(cmd.after_help, 0))),
+    ])
+}
+
+/// Collect every visible command in the tree, depth-first, with its full name.
+pub(crate) fn walk<'a>(
+    cmd: &'a Command<'a>,
+    name: &str,
+    sections: &mut Vec<(String, &'a Command<'a>)>,
+) {
+    sections.push((name.to_owned(), cmd));
+    for sub in commands(cmd) {
+        walk(sub, &format!("{name} {}", sub.names[0]), sections);
+    }
+}
+
+fn usages(name: &str, cmd: &Command) -> Vec<String> {
+    let usages = cmd.usages.iter().filter(|(.., hidden)| !hidden);
+    let usages = usages
+        .map(|(usage, ..)| format!("{name} {usage}"))
+        .collect::<Vec<_>>();
+
+    if usages.is_empty() {
+        vec![name.to_owned()]
+    } else {
+        usages
+    }
+}
+
+fn options<'a>(cmd: &Command<'a>, syntax: Syntax) -> Vec<(String, &'a str)> {
+    let options = cmd.options.iter().filter(|(.., hidden)| !hidden);
+    options
+        .map(|(opts, kind, value_name, doc, _)| {
+            (syntax.format_names(opts, *kind, value_name), *doc)
+        })
+        .collect()
+}
+
+fn commands<'a>(cmd: &Command<'a>) -> Vec<&'a Command<'a>> {
+    cmd.commands.iter().filter(|sub| !sub.hidden).collect()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,265 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, OnceLock};
+
+use crate::parser::Command;
+use crate::{shell, Args, Error, Opt, Style, Syntax};
+
+/// Run an interactive prompt, parsing each line with the same syntax as [`parse`](crate::parse).
+///
+/// Lines are split into words like a shell would. Errors and help messages are displayed without
+/// exiting, and the loop ends with the input, e.g. on Ctrl-D. A [`Repl`] can be given to
+/// customise the prompt, how lines are parsed, or to read lines with a line editor.
+///
+/// ```no_run
+/// sylveon::repl! {
+///     "user" {
+///         "add" {
+///             admin,
+///             name => println!("adding {name:?}"),
+///         },
+///     },
+///     "quit" => std::process::exit(0),
+/// }
+/// ```
+#[macro_export]
+macro_rules! repl {
+    ($repl:ident; $($rest:tt)*) => {{
+        use $crate::__priv::*;
+
+        const __COMMAND: $crate::__priv::Command<'static> = $crate::__command! { [] []; $($rest)* };
+        while let Some(mut __args) = $repl.next_args(&__COMMAND) {
+            if let Err(e) = $crate::try_parse! { __args; $($rest)* } {
+                $repl.report(&__args, &e);
+            }
+        }
+    }};
+    ($($rest:tt)*) => {{
+        let mut __repl = $crate::Repl::new();
+        $crate::repl! { __repl; $($rest)* }
+    }};
+}
+
+type ReadLine = dyn FnMut(&str) -> Option<String>;
+
+/// An interactive prompt, reading commands line by line. This can be given to
+/// [`repl`](crate::repl) to customise how lines are read.
+pub struct Repl {
+    prompt: String,
+    style: Style,
+    syntax: Syntax,
+    abbreviate: bool,
+    collect_errors: bool,
+    read: Box<ReadLine>,
+    completer: Completer,
+}
+
+impl Repl {
+    /// Create a prompt reading from standard input.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prompt: "> ".into(),
+            style: Style::default(),
+            syntax: Syntax::default(),
+            abbreviate: false,
+            collect_errors: false,
+            read: Box::new(read_stdin),
+            completer: Completer::default(),
+        }
+    }
+
+    /// Set the prompt shown before each line.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Set the [`Style`] for help and error messages.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set how options are written in each line, and completed, see [`Args::syntax`].
+    pub fn syntax(mut self, syntax: Syntax) -> Self {
+        self.syntax = syntax;
+        self
+    }
+
+    /// Allow abbreviating long options and subcommands in each line, see [`Args::abbreviate`].
+    pub fn abbreviate(mut self, enabled: bool) -> Self {
+        self.abbreviate = enabled;
+        self
+    }
+
+    /// Report every error in a line at once, see [`Args::collect_errors`].
+    pub fn collect_errors(mut self, enabled: bool) -> Self {
+        self.collect_errors = enabled;
+        self
+    }
+
+    /// Read lines with the given function instead of standard input, e.g. with a line editor. It
+    /// is given the prompt, and the loop ends once it returns [`None`].
+    pub fn read_with(mut self, read: impl FnMut(&str) -> Option<String> + 'static) -> Self {
+        self.read = Box::new(read);
+        self
+    }
+
+    /// Get a handle to complete lines, e.g. for a line editor.
+    #[must_use]
+    pub fn completer(&self) -> Completer {
+        self.completer.clone()
+    }
+
+    /// Read the next non-empty line. Used by [`repl`](crate::repl).
+    #[doc(hidden)]
+    pub fn next_args(&mut self, root: &'static Command<'static>) -> Option<Args> {
+        let _ = self.completer.0.set((root, self.syntax));
+
+        loop {
+            let line = (self.read)(&self.prompt)?;
+
+            match shell::split(&line) {
+                Ok(words) if words.is_empty() => {}
+                Ok(words) => {
+                    let args = Args::from(words).name("").style(self.style);
+                    let args = args.syntax(self.syntax).abbreviate(self.abbreviate);
+                    return Some(args.collect_errors(self.collect_errors));
+                }
+                Err(e) => {
+                    let _ = self
+                        .style
+                        .format_error(&e.to_string(), &mut io::stderr().lock());
+                }
+            }
+        }
+    }
+
+    /// Display an error or help message. Used by [`repl`](crate::repl).
+    #[doc(hidden)]
+    pub fn report(&self, args: &Args, error: &Error) {
+        let _ = match error {
+            Error::Help(_) | Error::HelpJson => error.render(args, &mut io::stdout().lock()),
+            _ => error.render(args, &mut io::stderr().lock()),
+        };
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_stdin(prompt: &str) -> Option<String> {
+    print!("{prompt}");
+    io::stdout().flush().ok()?;
+
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            println!();
+            None
+        }
+        Ok(_) => Some(line),
+    }
+}
+
+/// Completions for a [`Repl`]'s commands, available once the loop starts.
+#[derive(Clone, Default)]
+pub struct Completer(Arc<OnceLock<(&'static Command<'static>, Syntax)>>);
+
+impl Completer {
+    /// Get the possible words to replace the last word in `line` with, i.e. subcommands, or
+    /// options if the word starts like one, e.g. with `-` or `/` for [`Syntax::Dos`]. Options are
+    /// written in the [`Repl`]'s syntax. Hidden options and commands are left out.
+    #[must_use]
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let Some((mut cmd, syntax)) = self.0.get().copied() else {
+            return Vec::new();
+        };
+        let prefix = if syntax == Syntax::Dos { '/' } else { '-' };
+
+        let mut words = shell::split(line).unwrap_or_default();
+        let partial = if line.ends_with(char::is_whitespace) {
+            String::new()
+        } else {
+            words.pop().unwrap_or_default()
+        };
+
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            if word.starts_with(prefix) {
+                for _ in 0..arity(cmd, syntax, word) {
+                    words.next();
+                }
+            } else if let Some(sub) = cmd.find(word) {
+                cmd = sub;
+            }
+        }
+
+        let candidates: Vec<String> = if partial.starts_with(prefix) {
+            cmd.options
+                .iter()
+                .filter(|(.., hidden)| !hidden)
+                .flat_map(|(opts, ..)| opts.iter().map(|opt| syntax.format(opt)))
+                .collect()
+        } else {
+            cmd.commands
+                .iter()
+                .filter(|sub| !sub.hidden)
+                .flat_map(|sub| sub.names.iter().map(|name| name.to_string()))
+                .chain(cmd.has_help_command().then(|| "help".to_owned()))
+                .collect()
+        };
+
+        candidates
+            .into_iter()
+            .filter(|candidate| match candidate.get(..partial.len()) {
+                Some(start) if syntax == Syntax::Dos => start.eq_ignore_ascii_case(&partial),
+                start => start == Some(&partial),
+            })
+            .collect()
+    }
+}
+
+/// Get how many values follow an option, as typed.
+fn arity(cmd: &Command, syntax: Syntax, word: &str) -> usize {
+    let (long, separator) = match syntax {
+        Syntax::Unix | Syntax::KeyValue => (word.strip_prefix("--"), '='),
+        Syntax::SingleDash => (word.strip_prefix("--").or(word.strip_prefix('-')), '='),
+        Syntax::Dos => (word.strip_prefix('/'), ':'),
+    };
+
+    let find = |opt: Opt| {
+        let typed = syntax.format(&opt);
+        let mut options = cmd.options.iter();
+        options.find(|(opts, ..)| {
+            opts.iter().any(|o| match syntax {
+                Syntax::Dos => syntax.format(o).eq_ignore_ascii_case(&typed),
+                _ => syntax.format(o) == typed,
+            })
+        })
+    };
+
+    let option = long.and_then(|long| match long.split_once(separator) {
+        Some((name, _)) => Some((find(Opt::Long(name))?, 1)),
+        None => Some((find(Opt::Long(long))?, 0)),
+    });
+    // otherwise, the last of a cluster of short options, which DOS syntax doesn't have
+    let option = option.or_else(|| {
+        if syntax == Syntax::Dos || word.starts_with("--") {
+            return None;
+        }
+        Some((find(Opt::Short(word.chars().last()?))?, 0))
+    });
+
+    option.map_or(0, |((_, kind, ..), attached)| {
+        kind.arity().saturating_sub(attached)
+    })
+}
@@ -0,0 +1,102 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt;
+
+use crate::Args;
+
+impl Args {
+    /// Create an argument parser from a single string, e.g. from a config file or environment
+    /// variable, splitting it into words like a POSIX shell would.
+    ///
+    /// Words are separated by whitespace, and can be quoted with `'single'` or `"double"` quotes,
+    /// or escaped with backslashes. A `#` at the start of a word begins a comment, which lasts
+    /// until the end of the line. Variables and globs are not expanded.
+    ///
+    /// ```
+    /// let mut args = sylveon::Args::from_shell_str(r#"add "hello world" it\'s # a comment"#)?;
+    /// let words = sylveon::try_parse! { args; ..words => words }.unwrap();
+    ///
+    /// assert_eq!(words, ["add", "hello world", "it's"]);
+    /// # Ok::<(), sylveon::ShellError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If a quote is unterminated or the string ends with a backslash.
+    pub fn from_shell_str(line: &str) -> Result<Self, ShellError> {
+        split(line).map(Self::from)
+    }
+}
+
+/// A string couldn't be split into words, see [`Args::from_shell_str`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellError {
+    /// What went wrong
+    pub message: &'static str,
+    /// The byte offset in the string where the error starts, e.g. the unterminated quote
+    pub position: usize,
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// Split a line into words, following shell quoting rules.
+pub(crate) fn split(line: &str) -> Result<Vec<String>, ShellError> {
+    let mut words = Vec::new();
+    let mut word = None::<String>;
+    let mut chars = line.char_indices();
+
+    let error = |message, position| Err(ShellError { message, position });
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => words.extend(word.take()),
+            '#' if word.is_none() => {
+                chars.find(|&(_, c)| c == '\n');
+            }
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => word.push(c),
+                        None => return error("unterminated single quote", start),
+                    }
+                }
+            }
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, c @ ('"' | '\\' | '$' | '`'))) => word.push(c),
+                            Some((_, '\n')) => {}
+                            Some((_, c)) => word.extend(['\\', c]),
+                            None => return error("unterminated double quote", start),
+                        },
+                        Some((_, c)) => word.push(c),
+                        None => return error("unterminated double quote", start),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => word.get_or_insert_with(String::new).push(c),
+                None => return error("trailing backslash", start),
+            },
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    words.extend(word);
+    Ok(words)
+}
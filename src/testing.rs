@@ -0,0 +1,137 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers for testing command definitions, without exiting the process.
+//!
+//! ```
+//! use sylveon::{testing, Error, Verbosity};
+//!
+//! sylveon::assert_parse! { ["rm", "--force", "build"] => (true, Some("build".into()));
+//!     "rm" {
+//!         force,
+//!         path => (force, path),
+//!     }
+//! }
+//!
+//! let mut args = testing::args(["rm", "-x"]);
+//! let error = sylveon::try_parse! { args;
+//!     "rm" {
+//!         force,
+//!         path => (),
+//!     }
+//! }
+//! .unwrap_err();
+//!
+//! assert_eq!(
+//!     testing::render_error(&args, &error),
+//!     "error: unexpected argument: -x\n    sylveon rm -x\n               ^^\n",
+//! );
+//! ```
+
+use std::path::Path;
+use std::{env, fs};
+
+use crate::parser::Context;
+use crate::reference::walk;
+use crate::{Args, Error, Style, Verbosity};
+
+/// Create an argument parser from the given arguments, without colors.
+pub fn args<S: Into<String>>(args: impl IntoIterator<Item = S>) -> Args {
+    Args::from(args.into_iter().map(Into::into)).style(Style::plain())
+}
+
+/// Render the help message for the command being parsed, see [`Args::render_help`].
+pub fn render_help(args: &Args, verbosity: Verbosity) -> String {
+    let mut out = Vec::new();
+    args.render_help(verbosity, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// Render the message for an error, see [`Error::render`].
+pub fn render_error(args: &Args, error: &Error) -> String {
+    let mut out = Vec::new();
+    error.render(args, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+/// Compare the `--help` output of every command with the files in `dir`, e.g. `prog.txt` and
+/// `prog-remote-add.txt`.
+///
+/// If the `SYLVEON_UPDATE_SNAPSHOTS` environment variable is set, the files are written instead,
+/// which also creates missing ones. This is only available after [`try_parse`](crate::try_parse)
+/// starts, e.g. with `--help`.
+///
+/// # Panics
+///
+/// If any of the files differ or are missing, or can't be written.
+pub fn assert_help_snapshots(args: &Args, dir: impl AsRef<Path>) {
+    let dir = dir.as_ref();
+    let update = env::var_os("SYLVEON_UPDATE_SNAPSHOTS").is_some();
+    let mut sections = Vec::new();
+    walk(args.context.root, args.program(), &mut sections);
+
+    if update {
+        fs::create_dir_all(dir).unwrap();
+    }
+
+    let mut failed = Vec::new();
+    for (name, command) in sections {
+        let context = Context {
+            name: name.clone(),
+            command,
+            root: args.context.root,
+            syntax: args.context.syntax,
+            config: args.context.config.clone(),
+        };
+
+        let mut help = Vec::new();
+        args.style
+            .format_help(&context, Verbosity::Long, &mut help)
+            .unwrap();
+        let help = String::from_utf8(help).unwrap();
+
+        let path = dir.join(name.replace(' ', "-") + ".txt");
+        if update {
+            fs::write(&path, help).unwrap();
+            continue;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(snapshot) if snapshot != help => {
+                let path = path.display();
+                failed.push(format!("expected ({path}):\n{snapshot}\nfound:\n{help}"));
+            }
+            Ok(_) => {}
+            Err(_) => {
+                let path = path.display();
+                failed.push(format!("missing ({path}), found:\n{help}"));
+            }
+        }
+    }
+
+    assert!(
+        failed.is_empty(),
+        "help snapshots differ, set SYLVEON_UPDATE_SNAPSHOTS=1 to update them\n\n{}",
+        failed.join("\n"),
+    );
+}
+
+/// Assert that parsing the given arguments returns `expected`. The definition is the same as in
+/// [`try_parse`](crate::try_parse), and the bodies should return the values to compare.
+///
+/// # Panics
+///
+/// If parsing fails, showing the error message.
+#[macro_export]
+macro_rules! assert_parse {
+    ([$($arg:expr),* $(,)?] => $expected:expr; $($rest:tt)*) => {{
+        let mut __args = $crate::testing::args(::std::vec![$(::std::string::String::from($arg)),*]);
+
+        match $crate::try_parse! { __args; $($rest)* } {
+            Ok(v) => assert_eq!(v, $expected),
+            Err(e) => panic!("{}", $crate::testing::render_error(&__args, &e)),
+        }
+    }};
+}
@@ -0,0 +1,186 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+//! Parsers for common kinds of values, to be used with `#[parse(f)]`.
+//!
+//! ```
+//! use std::time::Duration;
+//! use sylveon::{testing, value};
+//!
+//! let mut args = testing::args(["--timeout", "1m30s", "--block", "4M"]);
+//! let (timeout, block) = sylveon::try_parse! { args;
+//!     #[parse(value::duration)]
+//!     timeout?,
+//!     #[parse(value::bytes)]
+//!     block?,
+//!     _ => (timeout, block),
+//! }
+//! .unwrap();
+//!
+//! assert_eq!(timeout, Some(Duration::from_secs(90)));
+//! assert_eq!(block, Some(4 << 20));
+//! ```
+//!
+//! Errors are reported like any other invalid value, e.g.
+//! `invalid value '5x' for '--timeout': expected a duration, like '10s' or '1h30m'`.
+//!
+//! Whitespace around values, and between numbers and their units, is ignored, except in paths.
+
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::ops::Bound;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Parse a duration made of numbers followed by a unit, like `10s`, `1.5h` or `1h30m`.
+///
+/// The units are `ms`, `s`, `m`, `h`, `d` and `w`.
+pub fn duration(value: &str) -> Result<Duration, String> {
+    let error = || "expected a duration, like '10s' or '1h30m'".to_owned();
+
+    let mut total = Duration::ZERO;
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return Err(error());
+    }
+
+    while !rest.is_empty() {
+        rest = rest.trim_start();
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .ok_or_else(error)?;
+        let (number, unit) = rest.split_at(end);
+        let number: f64 = number.parse().map_err(|_| error())?;
+
+        let unit = unit.trim_start();
+        let end = unit
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(unit.len());
+        let (unit, next) = unit.split_at(end);
+        let seconds = match unit {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 60.0 * 60.0,
+            "d" => 24.0 * 60.0 * 60.0,
+            "w" => 7.0 * 24.0 * 60.0 * 60.0,
+            _ => return Err(error()),
+        };
+
+        let part = Duration::try_from_secs_f64(number * seconds).map_err(|_| error())?;
+        total = total.checked_add(part).ok_or_else(error)?;
+        rest = next;
+    }
+
+    Ok(total)
+}
+
+/// Parse a size in bytes, like `512`, `4M`, `1.5GB` or `1GiB`.
+///
+/// Single letters are powers of 1024 as in `dd`, so `4M` is the same as `4MiB`, while `MB` and
+/// friends are powers of 1000. Units are case-insensitive.
+pub fn bytes(value: &str) -> Result<u64, String> {
+    let error = || "expected a size, like '512', '4M' or '1GiB'".to_owned();
+
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(end);
+    let number: f64 = number.parse().map_err(|_| error())?;
+
+    let unit = unit.trim_start().to_ascii_lowercase();
+    let (prefix, base) = match unit.strip_suffix("ib") {
+        Some(prefix) => (prefix, 1024f64),
+        None => match unit.strip_suffix('b') {
+            Some(prefix) if !prefix.is_empty() => (prefix, 1000f64),
+            _ => (unit.trim_end_matches('b'), 1024f64),
+        },
+    };
+    let power = match prefix {
+        "" => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        "p" => 5,
+        "e" => 6,
+        _ => return Err(error()),
+    };
+
+    let bytes = number * base.powi(power);
+    if bytes.fract() != 0.0 || bytes >= u64::MAX as f64 {
+        return Err(error());
+    }
+
+    Ok(bytes as u64)
+}
+
+/// Parse a range of values, like `1..10`, `1..=10`, `5..` or `..5`. Use it with a type, as in
+/// `#[parse(value::range::<u32>)]`.
+///
+/// The result can be used with anything taking a [`RangeBounds`](std::ops::RangeBounds).
+pub fn range<T: FromStr>(value: &str) -> Result<(Bound<T>, Bound<T>), String> {
+    let error = || "expected a range, like '1..10' or '1..=10'".to_owned();
+    let parse = |v: &str| v.trim().parse().map_err(|_| error());
+
+    let (start, end) = value.split_once("..").ok_or_else(error)?;
+    let start = match start.trim() {
+        "" => Bound::Unbounded,
+        v => Bound::Included(parse(v)?),
+    };
+    let end = match end.strip_prefix('=') {
+        Some(v) => Bound::Included(parse(v)?),
+        None if end.trim().is_empty() => Bound::Unbounded,
+        None => Bound::Excluded(parse(end)?),
+    };
+
+    Ok((start, end))
+}
+
+/// Parse a boolean, accepting `yes`/`no`, `on`/`off`, `true`/`false`, `y`/`n` and `1`/`0`.
+pub fn bool(value: &str) -> Result<bool, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "yes" | "on" | "true" | "y" | "1" => Ok(true),
+        "no" | "off" | "false" | "n" | "0" => Ok(false),
+        _ => Err("expected 'yes' or 'no'".to_owned()),
+    }
+}
+
+/// Parse the path to an existing file.
+pub fn file(value: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(value);
+    match path.metadata() {
+        Ok(meta) if meta.is_file() => Ok(path),
+        Ok(_) => Err("expected a file".to_owned()),
+        Err(_) => Err("expected an existing file".to_owned()),
+    }
+}
+
+/// Parse the path to an existing directory.
+pub fn dir(value: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(value);
+    match path.metadata() {
+        Ok(meta) if meta.is_dir() => Ok(path),
+        Ok(_) => Err("expected a directory".to_owned()),
+        Err(_) => Err("expected an existing directory".to_owned()),
+    }
+}
+
+/// Parse a socket address, like `127.0.0.1:8080`, `[::1]:8080` or `localhost:8080`.
+///
+/// Host names are resolved, and the first address is used.
+pub fn socket_addr(value: &str) -> Result<SocketAddr, String> {
+    let value = value.trim();
+    if let Ok(addr) = value.parse() {
+        return Ok(addr);
+    }
+
+    match value.to_socket_addrs() {
+        Ok(mut addrs) => addrs.next().ok_or_else(|| "no addresses found".to_owned()),
+        Err(_) if !value.contains(':') => Err("expected an address, like 'localhost:8080'".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
@@ -0,0 +1,69 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use sylveon::{testing, Error, Syntax};
+
+fn options(syntax: Syntax, args: &[&str]) -> Result<(bool, bool, bool), Error> {
+    let mut args = testing::args(args.iter().copied())
+        .syntax(syntax)
+        .abbreviate(true);
+    sylveon::try_parse! { args;
+        verbose,
+        verify,
+        v,
+        _ => (verbose, verify, v),
+    }
+}
+
+#[test]
+fn long_options() {
+    let result = options(Syntax::Unix, &["--verb", "--veri"]);
+    assert_eq!(result.unwrap(), (true, true, false));
+
+    let error = options(Syntax::Unix, &["--ver"]).unwrap_err();
+    assert!(matches!(error, Error::Ambiguous(v, _) if v == "--ver"));
+}
+
+#[test]
+fn single_dash() {
+    let result = options(Syntax::SingleDash, &["-verb", "-v"]);
+    assert_eq!(result.unwrap(), (true, false, true));
+
+    let error = options(Syntax::SingleDash, &["-ver"]).unwrap_err();
+    assert!(matches!(error, Error::Ambiguous(v, _) if v == "-ver"));
+}
+
+#[test]
+fn dos_ignores_case() {
+    let result = options(Syntax::Dos, &["/VERB"]);
+    assert_eq!(result.unwrap(), (true, false, false));
+}
+
+#[test]
+fn subcommands() {
+    let parse = |args: &[&str]| {
+        let mut args = testing::args(args.iter().copied()).abbreviate(true);
+        sylveon::try_parse! { args;
+            "status" => "status",
+            "stash" => "stash",
+            "hello" => "hello",
+        }
+    };
+
+    assert_eq!(parse(&["stat"]).unwrap(), "status");
+    assert!(matches!(parse(&["sta"]).unwrap_err(), Error::Ambiguous(..)));
+    assert!(matches!(parse(&["he"]).unwrap_err(), Error::Ambiguous(..)));
+}
+
+#[test]
+fn not_next_to_positionals() {
+    let mut args = testing::args(["he"]).abbreviate(true);
+    let result = sylveon::try_parse! { args;
+        "hello" => vec![],
+        ..rest => rest,
+    };
+
+    assert_eq!(result.unwrap(), ["he"]);
+}
@@ -0,0 +1,131 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+use sylveon::{testing, Error};
+
+type Parsed = (usize, Vec<String>, Option<String>, bool);
+
+/// Write a config file for a test, named after it.
+fn config(name: &str, text: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("sylveon-{name}-{}.toml", process::id()));
+    fs::write(&path, text).unwrap();
+    path
+}
+
+fn parse(args: sylveon::Args) -> Result<Parsed, String> {
+    let mut args = args;
+    let result = sylveon::try_parse! { args;
+        verbose+,
+        tags*,
+        out?,
+        "rm" {
+            force,
+            _ => (verbose, tags, out, force),
+        }
+    };
+
+    result.map_err(|e| testing::render_error(&args, &e))
+}
+
+const CONFIG: &str = "
+verbose = 2
+tags = ['a']
+out = 'config'
+
+[rm]
+force = true
+";
+
+#[test]
+fn config_file() {
+    let path = config("config-file", CONFIG);
+    let result = parse(testing::args(["rm"]).config(&path));
+
+    assert_eq!(
+        result,
+        Ok((2, vec!["a".into()], Some("config".into()), true))
+    );
+}
+
+#[test]
+fn command_line_overrides_config() {
+    let path = config("overrides", CONFIG);
+    let args = testing::args(["--verbose", "--tags", "x", "--out=cli", "rm"]);
+    let result = parse(args.config(&path));
+
+    assert_eq!(result, Ok((1, vec!["x".into()], Some("cli".into()), true)));
+}
+
+#[test]
+fn env_overrides_config() {
+    let path = config("env", CONFIG);
+    env::set_var("SYLVEON_TEST_ENV_OPTS", "--out env --verbose");
+    let args = testing::args(["rm"]).config(&path);
+    let result = parse(args.env("SYLVEON_TEST_ENV_OPTS"));
+
+    assert_eq!(result, Ok((1, vec!["a".into()], Some("env".into()), true)));
+}
+
+#[test]
+fn env_error() {
+    env::set_var("SYLVEON_TEST_ENV_ERROR", "--bad");
+    let result = parse(testing::args(["rm"]).env("SYLVEON_TEST_ENV_ERROR"));
+
+    assert_eq!(
+        result.unwrap_err(),
+        "error: in $SYLVEON_TEST_ENV_ERROR: unexpected argument: --bad\n    --bad\n    ^^^^^\n",
+    );
+}
+
+#[test]
+fn help_with_invalid_config() {
+    let path = config("invalid", "verbose = 'yes'");
+
+    let mut args = testing::args(["--help"]).config(&path);
+    let result = sylveon::try_parse! { args; verbose+, _ => verbose };
+    assert!(matches!(result, Err(Error::Help(_))));
+    assert!(testing::render_help(&args, sylveon::Verbosity::Long).contains("--config <path>"));
+
+    let mut args = testing::args(Vec::<String>::new()).config(&path);
+    let result = sylveon::try_parse! { args; verbose+, _ => verbose };
+    assert!(matches!(result, Err(Error::Config(_))));
+}
+
+#[test]
+fn explicit_config() {
+    let path = config("explicit", "out = 'explicit'");
+    let args = testing::args(["--config", path.to_str().unwrap(), "rm"]);
+    let result = parse(args.config("/nonexistent"));
+
+    assert_eq!(result, Ok((0, vec![], Some("explicit".into()), false)));
+}
+
+#[test]
+fn declared_config_option() {
+    let path = config("declared", "out = 'declared'");
+    let path = path.to_str().unwrap();
+
+    let mut args = testing::args(["--config", path]).config("/nonexistent");
+    let result = sylveon::try_parse! { args; config?, out?, _ => (config, out) };
+    assert_eq!(
+        result.unwrap(),
+        (Some(path.into()), Some("declared".into()))
+    );
+
+    let help = testing::render_help(&args, sylveon::Verbosity::Long);
+    assert_eq!(help.matches("--config").count(), 1, "{help}");
+}
+
+#[test]
+fn switches_stay_on() {
+    // there's no way to turn a switch off, so the file's value always applies
+    let path = config("switches", "[rm]\nforce = true");
+    let result = parse(testing::args(["rm"]).config(&path));
+
+    assert_eq!(result, Ok((0, vec![], None, true)));
+}
@@ -0,0 +1,114 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use sylveon::{testing, Error, Span};
+
+fn parse(args: sylveon::Args) -> (sylveon::Args, Result<(), Error>) {
+    let mut args = args;
+    let result = sylveon::try_parse! { args;
+        #[validate(|v| v.parse::<u16>())]
+        port?,
+        size?[2],
+        "user" {
+            "add" {
+                name => drop(name),
+            }
+        }
+    };
+
+    (args, result)
+}
+
+fn render(args: sylveon::Args) -> String {
+    let (args, result) = parse(args);
+    testing::render_error(&args, &result.unwrap_err())
+}
+
+#[test]
+fn unexpected_argument() {
+    assert_eq!(
+        render(testing::args(["user", "add", "--x"])),
+        "error: unexpected argument: --x\n    sylveon user add --x\n                     ^^^\n",
+    );
+}
+
+#[test]
+fn missing_value() {
+    assert_eq!(
+        render(testing::args(["--port"])),
+        "error: option '--port' requires a value\n    sylveon --port\n            ^^^^^^\n",
+    );
+}
+
+#[test]
+fn invalid_value() {
+    assert_eq!(
+        render(testing::args(["--port=99999", "user"])),
+        "error: invalid value '99999' for '--port': number too large to fit in target type\n    \
+         sylveon --port=99999 user\n                   ^^^^^\n",
+    );
+}
+
+#[test]
+fn empty_program_name() {
+    assert_eq!(
+        render(testing::args(["user", "add", "--x"]).name("")),
+        "error: unexpected argument: --x\n    user add --x\n             ^^^\n",
+    );
+}
+
+#[test]
+fn error_span() {
+    let (args, result) = parse(testing::args(["group"]));
+
+    assert!(matches!(result, Err(Error::UnknownCommand(v)) if v == "group"));
+    assert!(matches!(
+        args.error_span(),
+        Some(Span {
+            origin: None,
+            index: 0,
+            ..
+        }),
+    ));
+}
+
+#[test]
+fn collect_errors() {
+    let args = testing::args(["--bogus=1", "user", "add", "x"]).collect_errors(true);
+    let (_, result) = parse(args);
+    assert!(
+        matches!(&result, Err(Error::Unexpected(v)) if v == "--bogus"),
+        "{result:?}"
+    );
+
+    let args = testing::args(["--port", "x", "--size", "1"]).collect_errors(true);
+    let (args, result) = parse(args);
+
+    let Err(Error::Multiple(errors)) = result else {
+        panic!("expected several errors, found {result:?}");
+    };
+    assert!(matches!(errors[0], Error::Invalid { .. }));
+    assert_eq!(args.error_spans().len(), 2);
+    assert!(matches!(
+        errors[1],
+        Error::MissingValues {
+            expected: 2,
+            found: 1,
+            ..
+        },
+    ));
+}
+
+#[test]
+fn help_json() {
+    let (args, result) = parse(testing::args(["user", "--help-json"]));
+    let error = result.unwrap_err();
+    assert!(matches!(error, Error::HelpJson), "{error:?}");
+
+    let mut json = Vec::new();
+    error.render(&args, &mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert!(json.starts_with('{') && json.contains("\"add\""), "{json}");
+}
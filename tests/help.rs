@@ -0,0 +1,72 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use sylveon::{testing, Error, Verbosity};
+
+fn parse(args: &[&str]) -> (sylveon::Args, Result<Vec<String>, Error>) {
+    let mut args = testing::args(args.iter().copied()).abbreviate(true);
+    let result = sylveon::try_parse! { args;
+        "add" {
+            /// The file to add
+            file => Vec::from_iter(file),
+        },
+        _ => vec![],
+    };
+
+    (args, result)
+}
+
+#[test]
+fn help_command() {
+    let (args, result) = parse(&["help", "add"]);
+    assert!(matches!(result, Err(Error::Help(_))), "{result:?}");
+
+    let help = testing::render_help(&args, Verbosity::Short);
+    assert!(help.contains("The file to add"), "{help}");
+}
+
+#[test]
+fn with_fallback() {
+    let (args, result) = parse(&["help"]);
+    assert!(matches!(result, Err(Error::Help(_))), "{result:?}");
+
+    let help = testing::render_help(&args, Verbosity::Short);
+    assert!(help.contains("help [command]"), "{help}");
+
+    let (_, result) = parse(&["hel"]);
+    assert!(matches!(result, Err(Error::Help(_))), "{result:?}");
+    let (_, result) = parse(&[]);
+    assert_eq!(result.unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn with_positionals() {
+    let parse = |args: &[&str]| {
+        let mut args = testing::args(args.iter().copied());
+        let result = sylveon::try_parse! { args;
+            "add" => vec![],
+            ..files => files,
+        };
+        (args, result)
+    };
+
+    let (args, result) = parse(&["help"]);
+    assert!(matches!(result, Err(Error::Help(_))), "{result:?}");
+    let help = testing::render_help(&args, Verbosity::Short);
+    assert!(help.contains("help [command]"), "{help}");
+
+    let (_, result) = parse(&["a", "help"]);
+    assert_eq!(result.unwrap(), ["a", "help"]);
+}
+
+#[test]
+fn only_with_subcommands() {
+    let mut args = testing::args(["help"]);
+    let result = sylveon::try_parse! { args; ..rest => rest };
+    assert_eq!(result.unwrap(), ["help"]);
+
+    let help = testing::render_help(&args, Verbosity::Short);
+    assert!(!help.contains("help [command]"), "{help}");
+}
@@ -0,0 +1,30 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeMap;
+
+use sylveon::{testing, Error};
+
+fn parse(args: &[&str]) -> Result<BTreeMap<String, u16>, Error> {
+    let mut args = testing::args(args.iter().copied());
+    sylveon::try_parse! { args;
+        #[validate(|v| v.parse::<u8>())]
+        #[parse(|v| v.parse::<u16>())]
+        limit{},
+        _ => limit,
+    }
+}
+
+#[test]
+fn validate_and_parse_values() {
+    let limits = parse(&["--limit", "a=1", "--limit", "b=2"]).unwrap();
+    assert_eq!(limits, BTreeMap::from([("a".into(), 1), ("b".into(), 2)]));
+
+    let error = parse(&["--limit", "a=300"]).unwrap_err();
+    assert!(matches!(error, Error::Invalid { value, .. } if value == "300"));
+
+    let error = parse(&["--limit", "a"]).unwrap_err();
+    assert!(matches!(error, Error::Invalid { message, .. } if message == "expected 'key=value'"));
+}
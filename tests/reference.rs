@@ -0,0 +1,106 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use sylveon::Reference;
+
+fn reference() -> Reference {
+    sylveon::reference! {
+        //! A tiny package manager
+
+        /// Increase output
+        verbose+,
+
+        /// Add a dependency
+        "add" {
+            /// Add as a development dependency
+            dev,
+            name => todo!(),
+        },
+        #[hidden]
+        "docs" => todo!(),
+    }
+    .name("pkg")
+}
+
+fn write(f: impl FnOnce(&Reference, &mut Vec<u8>) -> std::io::Result<()>) -> String {
+    let mut out = Vec::new();
+    f(&reference(), &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn markdown() {
+    assert_eq!(
+        write(Reference::write_markdown),
+        "# `pkg`\n\nA tiny package manager\n\n```text\npkg add\npkg help [command]\n```\n\n\
+         | Option | Description |\n| --- | --- |\n| `--verbose` | Increase output |\n\n\
+         | Command | Description |\n| --- | --- |\n| `add` | Add a dependency |\n\n\
+         ## `pkg add`\n\nAdd a dependency\n\n```text\npkg add [name]\n```\n\n\
+         | Option | Description |\n| --- | --- |\n| `--dev` | Add as a development dependency |\n",
+    );
+}
+
+#[test]
+fn html() {
+    let html = write(Reference::write_html);
+
+    assert!(
+        html.starts_with("<section id=\"pkg\">\n<h2><code>pkg</code></h2>\n"),
+        "{html}"
+    );
+    assert!(
+        html.contains(
+            "<dt><a href=\"#pkg-add\"><code>add</code></a></dt>\n<dd>Add a dependency</dd>"
+        ),
+        "{html}"
+    );
+    assert!(html.contains("<section id=\"pkg-add\">"), "{html}");
+    assert!(!html.contains("docs"), "{html}");
+    assert_eq!(
+        html.matches("<section").count(),
+        html.matches("</section>").count()
+    );
+}
+
+#[test]
+fn json() {
+    let json = write(Reference::write_json);
+
+    assert!(json.starts_with("{\n  \"name\": \"pkg\",\n"), "{json}");
+    assert!(json.contains("\"kind\": \"count\""), "{json}");
+    // hidden commands are included, and marked as such
+    assert!(
+        json.contains("\"name\": \"docs\",\n      \"aliases\": [],\n      \"description\": \"\",\n      \"hidden\": true"),
+        "{json}"
+    );
+}
+
+#[test]
+fn same_as_parsing() {
+    let mut args = sylveon::testing::args(["docs"]).name("pkg");
+    let result = sylveon::try_parse! { args;
+        //! A tiny package manager
+
+        /// Increase output
+        verbose+,
+
+        /// Add a dependency
+        "add" {
+            /// Add as a development dependency
+            dev,
+            name => drop((verbose, dev, name)),
+        },
+        #[hidden]
+        "docs" => (),
+    };
+    result.unwrap();
+
+    let mut markdown = Vec::new();
+    args.write_markdown(&mut markdown).unwrap();
+    assert_eq!(
+        String::from_utf8(markdown).unwrap(),
+        write(Reference::write_markdown)
+    );
+}
@@ -0,0 +1,95 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use sylveon::{Repl, Syntax};
+
+/// A prompt reading the given lines, then ending.
+fn lines(lines: &[&str]) -> Repl {
+    let mut lines: Vec<String> = lines.iter().rev().map(ToString::to_string).collect();
+    Repl::new().read_with(move |_| lines.pop())
+}
+
+#[test]
+fn runs_each_line() {
+    let ran = Rc::new(RefCell::new(Vec::new()));
+    let mut repl = lines(&["add a", "", "add --force b", "bogus", "add 'c d'"]);
+    sylveon::repl! { repl;
+        "add" {
+            force,
+            name => ran.borrow_mut().push((name, force)),
+        },
+    }
+
+    assert_eq!(
+        *ran.borrow(),
+        [
+            (Some("a".into()), false),
+            (Some("b".into()), true),
+            (Some("c d".into()), false),
+        ],
+    );
+}
+
+#[test]
+fn parses_with_settings() {
+    let ran = Rc::new(RefCell::new(Vec::new()));
+    let repl = lines(&["st /OUT:x", "st /ou y"]);
+    let mut repl = repl.syntax(Syntax::Dos).abbreviate(true);
+    sylveon::repl! { repl;
+        "status" {
+            out?,
+            _ => ran.borrow_mut().push(out),
+        },
+    }
+
+    assert_eq!(*ran.borrow(), [Some("x".into()), Some("y".into())]);
+}
+
+#[test]
+fn completes() {
+    let mut repl = lines(&[]);
+    let completer = repl.completer();
+    assert!(completer.complete("").is_empty());
+
+    sylveon::repl! { repl;
+        "user" {
+            "add" {
+                admin,
+                out?,
+                name => drop((admin, out, name)),
+            },
+            "remove" => (),
+        },
+        #[hidden]
+        "debug" => (),
+    }
+
+    assert_eq!(completer.complete(""), ["user", "help"]);
+    assert_eq!(completer.complete("user "), ["add", "remove", "help"]);
+    assert_eq!(completer.complete("user add --"), ["--admin", "--out"]);
+    assert_eq!(completer.complete("user a"), ["add"]);
+    assert!(completer.complete("user add --out add ").is_empty());
+}
+
+#[test]
+fn completes_in_syntax() {
+    let mut repl = lines(&[]).syntax(Syntax::Dos);
+    let completer = repl.completer();
+    sylveon::repl! { repl;
+        "add" {
+            out?,
+            "now" => drop(out),
+        },
+    }
+
+    assert_eq!(completer.complete("add /"), ["/out"]);
+    assert_eq!(completer.complete("add /O"), ["/out"]);
+    assert_eq!(completer.complete("add /out now n"), ["now"]);
+    assert_eq!(completer.complete("add /out:now n"), ["now"]);
+    assert!(completer.complete("add /out now now ").is_empty());
+}
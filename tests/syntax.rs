@@ -0,0 +1,121 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use sylveon::{testing, Error, Syntax};
+
+type Parsed = (bool, usize, Option<String>, Vec<String>);
+
+fn parse(syntax: Syntax, args: &[&str]) -> Result<Parsed, String> {
+    let mut args = testing::args(args.iter().copied()).syntax(syntax);
+    let result = sylveon::try_parse! { args;
+        force = f | force,
+        verbose+ = v | verbose,
+        out? = o | out,
+        ..rest => (force, verbose, out, rest),
+    };
+
+    result.map_err(|e| testing::render_error(&args, &e))
+}
+
+#[test]
+fn unix() {
+    assert_eq!(
+        parse(Syntax::Unix, &["-fvv", "--out=a", "b", "--", "-f"]),
+        Ok((true, 2, Some("a".into()), vec!["b".into(), "-f".into()])),
+    );
+    assert_eq!(
+        parse(Syntax::Unix, &["-oa", "--verbose"]),
+        Ok((false, 1, Some("a".into()), vec![])),
+    );
+    assert_eq!(
+        parse(Syntax::Unix, &["-o", "a"]),
+        Ok((false, 0, Some("a".into()), vec![])),
+    );
+}
+
+#[test]
+fn unix_empty_attached_value() {
+    assert_eq!(
+        parse(Syntax::Unix, &["--out="]),
+        Ok((false, 0, Some("".into()), vec![])),
+    );
+}
+
+#[test]
+fn single_dash() {
+    assert_eq!(
+        parse(
+            Syntax::SingleDash,
+            &["-force", "-out=a", "--verbose", "-vf"]
+        ),
+        Ok((true, 2, Some("a".into()), vec![])),
+    );
+    assert_eq!(
+        parse(Syntax::SingleDash, &["-bar"]).unwrap_err(),
+        "error: unexpected argument: -bar\n    sylveon -bar\n            ^^^^\n",
+    );
+}
+
+#[test]
+fn dos() {
+    assert_eq!(
+        parse(Syntax::Dos, &["/F", "/VERBOSE", "/Out:a", "b"]),
+        Ok((true, 1, Some("a".into()), vec!["b".into()])),
+    );
+    assert_eq!(
+        parse(Syntax::Dos, &["/Bogus:1"]).unwrap_err(),
+        "error: unexpected argument: /Bogus\n    sylveon /Bogus:1\n            ^^^^^^\n",
+    );
+}
+
+#[test]
+fn dos_keeps_declared_case() {
+    let mut args = testing::args(["/v", "/dryrun"]).syntax(Syntax::Dos);
+    let result = sylveon::try_parse! { args;
+        version = V,
+        dry_run = dryRun,
+        _ => (version, dry_run),
+    };
+
+    assert_eq!(result.unwrap(), (true, true));
+}
+
+#[test]
+fn dos_help() {
+    let mut args = testing::args(["/?"]).syntax(Syntax::Dos);
+    let result = sylveon::try_parse! { args; force, _ => force };
+
+    assert!(matches!(result, Err(Error::Help(_))));
+    assert!(testing::render_help(&args, sylveon::Verbosity::Short).contains("/force"));
+
+    for help in ["/h", "/H", "/HELP"] {
+        let mut args = testing::args([help]).syntax(Syntax::Dos);
+        let result = sylveon::try_parse! { args; force, _ => force };
+        assert!(matches!(result, Err(Error::Help(_))), "{help}");
+    }
+}
+
+#[test]
+fn key_value_help() {
+    let mut args = testing::args(["--help"]).syntax(Syntax::KeyValue);
+    let result = sylveon::try_parse! { args; out?, color?("always"), _ => (out, color) };
+    assert!(matches!(result, Err(Error::Help(_))));
+
+    let help = testing::render_help(&args, sylveon::Verbosity::Short);
+    assert!(help.contains("out=<value>"), "{help}");
+    assert!(help.contains("color=<value>"), "{help}");
+
+    let mut args = testing::args(["color=never"]).syntax(Syntax::KeyValue);
+    let result = sylveon::try_parse! { args; color?("always"), _ => color };
+    assert_eq!(result.unwrap(), Some("never".into()));
+}
+
+#[test]
+fn key_value() {
+    assert_eq!(
+        parse(Syntax::KeyValue, &["out=a", "-f", "b=c"]),
+        Ok((true, 0, Some("a".into()), vec!["b=c".into()])),
+    );
+}
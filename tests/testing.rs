@@ -0,0 +1,33 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{env, process};
+
+use sylveon::testing;
+
+#[test]
+fn subcommand_values() {
+    sylveon::assert_parse! { ["log"] => "log";
+        "status" => "status",
+        "log" => "log",
+    }
+    sylveon::assert_parse! { ["status", "--short"] => "status";
+        "status" {
+            short,
+            _ => assert!(short),
+        } => "status",
+        "log" => "log",
+    }
+}
+
+#[test]
+#[should_panic = "missing"]
+fn missing_snapshots() {
+    let mut args = testing::args(["--help"]);
+    let _ = sylveon::try_parse! { args; force, _ => force };
+
+    let dir = env::temp_dir().join(format!("sylveon-snapshots-{}", process::id()));
+    testing::assert_help_snapshots(&args, dir);
+}
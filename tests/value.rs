@@ -0,0 +1,114 @@
+// sylveon, a tiny cli parser
+// Copyright (c) 2024 rini
+//
+// SPDX-License-Identifier: Apache-2.0
+
+use std::env;
+use std::ops::Bound;
+use std::time::Duration;
+
+use sylveon::value;
+
+#[test]
+fn duration() {
+    let secs = |s| Ok(Duration::from_secs(s));
+    assert_eq!(value::duration("10s"), secs(10));
+    assert_eq!(value::duration("1h30m"), secs(90 * 60));
+    assert_eq!(value::duration("1.5m"), secs(90));
+    assert_eq!(value::duration("2d 1w"), secs(9 * 24 * 60 * 60));
+    assert_eq!(value::duration("10 s"), secs(10));
+    assert_eq!(value::duration(" 1h 30m "), secs(90 * 60));
+    assert_eq!(value::duration("250ms"), Ok(Duration::from_millis(250)));
+
+    for invalid in ["", "10", "s", "10x", "1.2.3s", "-1s", "1e3s"] {
+        assert!(value::duration(invalid).is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn duration_overflow() {
+    assert!(value::duration("20000000000000w 20000000000000w").is_err());
+    assert!(value::duration("99999999999999999999w").is_err());
+}
+
+#[test]
+fn bytes() {
+    assert_eq!(value::bytes("512"), Ok(512));
+    assert_eq!(value::bytes("4M"), Ok(4 << 20));
+    assert_eq!(value::bytes("4MiB"), Ok(4 << 20));
+    assert_eq!(value::bytes("4 m"), Ok(4 << 20));
+    assert_eq!(value::bytes("1.5KB"), Ok(1500));
+    assert_eq!(value::bytes("1kb"), Ok(1000));
+    assert_eq!(value::bytes("2B"), Ok(2));
+
+    for invalid in ["", "M", "1.5", "4X", "0.1B", "1000E"] {
+        assert!(value::bytes(invalid).is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn range() {
+    use Bound::*;
+
+    assert_eq!(
+        value::range::<u32>("1..10"),
+        Ok((Included(1), Excluded(10)))
+    );
+    assert_eq!(
+        value::range::<u32>("1..=10"),
+        Ok((Included(1), Included(10)))
+    );
+    assert_eq!(value::range::<u32>("5.."), Ok((Included(5), Unbounded)));
+    assert_eq!(value::range::<u32>("..5"), Ok((Unbounded, Excluded(5))));
+    assert_eq!(
+        value::range::<u32>(" 1 .. 2 "),
+        Ok((Included(1), Excluded(2)))
+    );
+
+    for invalid in ["", "1", "a..b", "1..=", "1...2"] {
+        assert!(value::range::<u32>(invalid).is_err(), "{invalid:?}");
+    }
+}
+
+#[test]
+fn bool() {
+    for yes in ["yes", "on", "true", "y", "1", " YES "] {
+        assert_eq!(value::bool(yes), Ok(true), "{yes:?}");
+    }
+    for no in ["no", "off", "false", "n", "0", "No"] {
+        assert_eq!(value::bool(no), Ok(false), "{no:?}");
+    }
+    assert_eq!(value::bool("maybe"), Err("expected 'yes' or 'no'".into()));
+}
+
+#[test]
+fn paths() {
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    let dir = env!("CARGO_MANIFEST_DIR");
+
+    assert_eq!(value::file(manifest), Ok(manifest.into()));
+    assert_eq!(value::file(dir), Err("expected a file".into()));
+    assert_eq!(
+        value::file("/nonexistent"),
+        Err("expected an existing file".into()),
+    );
+
+    assert_eq!(value::dir(dir), Ok(dir.into()));
+    assert_eq!(value::dir(manifest), Err("expected a directory".into()));
+    assert_eq!(
+        value::dir("/nonexistent"),
+        Err("expected an existing directory".into()),
+    );
+}
+
+#[test]
+fn socket_addr() {
+    let addr = |s: &str| Ok(s.parse().unwrap());
+    assert_eq!(value::socket_addr("127.0.0.1:8080"), addr("127.0.0.1:8080"));
+    assert_eq!(value::socket_addr("[::1]:80"), addr("[::1]:80"));
+    assert_eq!(value::socket_addr(" 127.0.0.1:80 "), addr("127.0.0.1:80"));
+    assert_eq!(
+        value::socket_addr("localhost"),
+        Err("expected an address, like 'localhost:8080'".into()),
+    );
+}